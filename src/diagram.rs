@@ -0,0 +1,288 @@
+use crate::parse::*;
+
+// index of an edge in Diagram::edges
+pub type EdgeId = usize;
+
+// where an edge meets a box, the cell just outside the border it ends on and
+// the side of the box that's on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Port {
+	pub id: BoxId,
+	pub side: Direction,
+	pub at: Point,
+}
+
+// the text inside box b, leaving out anything skip says belongs to something
+// else (a box inside it, or a line running through it)
+//
+//  ,--------.
+//  | hello  |
+//  |  there |  -> (2, 3) "hello", (3, 4) "there"
+//  '--------'
+fn label(lines: &Lines, b: TBox, skip: impl Fn(Point) -> bool) -> Label {
+	let mut ret = vec![];
+	for row in b.0.row + 1..b.1.row {
+		let cells: Vec<(Point, char)> = (b.0.col + 1..b.1.col)
+			.map(|col| Point { row, col })
+			.map(|p| match lines.at(p) {
+				Some(c) if !skip(p) => (p, c),
+				_ => (p, ' '),
+			})
+			.collect();
+		let fst = cells.iter().position(|(_, c)| !c.is_whitespace());
+		let lst = cells.iter().rposition(|(_, c)| !c.is_whitespace());
+		if let (Some(fst), Some(lst)) = (fst, lst) {
+			let text = cells[fst..=lst].iter().map(|(_, c)| c).collect();
+			ret.push((cells[fst].0, text));
+		}
+	}
+	Label(ret)
+}
+
+// the boxes and edges of a drawing, ids are indexes into these, and since
+// boxes are found in reading order of their top left corners the same drawing
+// always gets the same ids
+pub struct Diagram {
+	pub boxes: Vec<TBox>,
+	// the text inside each box
+	pub labels: Vec<Label>,
+	// the smallest box each box is drawn inside of
+	pub parents: Vec<Option<BoxId>>,
+	pub edges: Vec<Edge>,
+}
+
+//  ,-----------.
+//  | ,-.  ,--. |  a box's parent is the smallest other box around it, so the
+//  | | | ,'--' |  boxes form a tree (or a few)
+//  | '-'   ,-. |
+//  '-------'-'-'
+fn parents(boxes: &[TBox]) -> Vec<Option<BoxId>> {
+	let area = |b: &TBox| (b.1.row - b.0.row + 1) * (b.1.col - b.0.col + 1);
+	boxes
+		.iter()
+		.map(|b| {
+			boxes
+				.iter()
+				.enumerate()
+				.filter(|(_, o)| *o != b && o.contains(b.0) && o.contains(b.1))
+				.min_by_key(|(_, o)| area(o))
+				.map(|(i, _)| i)
+		})
+		.collect()
+}
+
+impl Diagram {
+	pub fn parse(lines: &Lines) -> Diagram {
+		let boxes = boxes(lines);
+		let edges = edges(lines, &boxes);
+		let parents = parents(&boxes);
+		let labels = (0..boxes.len())
+			.map(|i| {
+				label(lines, boxes[i], |p| {
+					(0..boxes.len()).any(|o| parents[o] == Some(i) && boxes[o].contains(p))
+						|| edges.iter().any(|e| path_contains(&e.path, p))
+				})
+			})
+			.collect();
+		Diagram {
+			boxes,
+			labels,
+			parents,
+			edges,
+		}
+	}
+
+	pub fn children(&self, id: BoxId) -> impl Iterator<Item = BoxId> + '_ {
+		(0..self.boxes.len()).filter(move |o| self.parents[*o] == Some(id))
+	}
+
+	// the boxes that aren't inside any other
+	pub fn roots(&self) -> impl Iterator<Item = BoxId> + '_ {
+		(0..self.boxes.len()).filter(move |o| self.parents[*o].is_none())
+	}
+
+	// boxes whose border edge e runs across, going in or out of them
+	pub fn crossed(&self, e: EdgeId) -> Vec<BoxId> {
+		let cells = path_points(&self.edges[e].path);
+		(0..self.boxes.len())
+			.filter(|i| cells.iter().any(|p| self.boxes[*i].on_border(*p)))
+			.collect()
+	}
+
+	// edges running across box id's border, into (or out of) it
+	pub fn entering(&self, id: BoxId) -> impl Iterator<Item = EdgeId> + '_ {
+		(0..self.edges.len()).filter(move |e| self.crossed(*e).contains(&id))
+	}
+
+	// what to call a box, its label if it has one
+	pub fn name(&self, id: BoxId) -> String {
+		match self.labels[id].name() {
+			s if s.is_empty() => format!("box{}", id),
+			s => s,
+		}
+	}
+
+	// the first box (so the outermost, if they nest) that p is in
+	pub fn box_at(&self, p: Point) -> Option<BoxId> {
+		self.boxes.iter().position(|b| b.contains(p))
+	}
+
+	fn port(&self, id: Option<BoxId>, at: Point) -> Option<Port> {
+		let id = id?;
+		border(self.boxes[id])
+			.into_iter()
+			.find(|(q, _)| *q == at)
+			.map(|(_, side)| Port { id, side, at })
+	}
+
+	// the ends of edge e that are attached to boxes, from end first
+	pub fn ports(&self, e: EdgeId) -> (Option<Port>, Option<Port>) {
+		let e = &self.edges[e];
+		(
+			self.port(e.from, e.path[0]),
+			self.port(e.to, e.path[e.path.len() - 1]),
+		)
+	}
+
+	// the edges with an end on box id
+	pub fn attached(&self, id: BoxId) -> impl Iterator<Item = EdgeId> + '_ {
+		self.edges
+			.iter()
+			.enumerate()
+			.filter(move |(_, e)| e.from == Some(id) || e.to == Some(id))
+			.map(|(i, _)| i)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_nesting() {
+		let lines = Lines(
+			r#"
+ ,-----------.
+ | ,---.     |     ,-.
+ | | a |-----|-----| |
+ | '---'     |     '-'
+ | ,-.       |
+ | |c|       |
+ | '-'       |
+ '-----------'
+"#
+			.lines()
+			.map(|l| l.chars().collect())
+			.collect(),
+		);
+		let dia = Diagram::parse(&lines);
+		assert_eq!(4, dia.boxes.len());
+		assert_eq!(vec![None, Some(0), None, Some(0)], dia.parents);
+		assert_eq!(vec![1, 3], dia.children(0).collect::<Vec<_>>());
+		assert_eq!(vec![0, 2], dia.roots().collect::<Vec<_>>());
+
+		// the line from a runs out through the outer box to the one outside
+		assert_eq!(1, dia.edges.len());
+		assert_eq!((Some(1), Some(2)), (dia.edges[0].from, dia.edges[0].to));
+		assert_eq!(vec![0], dia.crossed(0));
+		assert_eq!(vec![0], dia.entering(0).collect::<Vec<_>>());
+		assert_eq!(0, dia.entering(1).count());
+	}
+
+	#[test]
+	fn test_labels() {
+		let lines = Lines(
+			r#"
+ ,------.
+ |  hi  |  ,------------.
+ '------'  | auth  ,--. |
+           |  api  |0 | |
+           '-------'--'-'
+"#
+			.lines()
+			.map(|l| l.chars().collect())
+			.collect(),
+		);
+		let dia = Diagram::parse(&lines);
+		assert_eq!(3, dia.boxes.len());
+		assert_eq!(
+			vec![(Point { row: 2, col: 4 }, "hi".to_string())],
+			dia.labels[0].0
+		);
+		// the box inside isn't part of the text
+		assert_eq!(
+			vec![
+				(Point { row: 3, col: 13 }, "auth".to_string()),
+				(Point { row: 4, col: 14 }, "api".to_string()),
+			],
+			dia.labels[1].0
+		);
+		assert_eq!("auth\napi", dia.labels[1].text());
+		assert_eq!("auth api", dia.name(1));
+		assert_eq!("0", dia.name(2));
+		assert_eq!("hi", dia.name(0));
+	}
+
+	#[test]
+	fn test_diagram() {
+		use Direction::*;
+		let lines = Lines(
+			r#"
+ ,-.    ,-.
+ | |--->| |
+ '-'    '-'
+  |      ^
+  |      |
+ ,-.     |
+ | |<----'
+ '-'---->
+"#
+			.lines()
+			.map(|l| l.chars().collect())
+			.collect(),
+		);
+		let dia = Diagram::parse(&lines);
+		assert_eq!(3, dia.boxes.len());
+		assert_eq!(4, dia.edges.len());
+		assert_eq!(Some(2), dia.box_at(Point { row: 7, col: 2 }));
+
+		assert_eq!(
+			(2, 2, 3),
+			(
+				dia.attached(0).count(),
+				dia.attached(1).count(),
+				dia.attached(2).count()
+			)
+		);
+
+		// the undirected edge between the top left and bottom boxes
+		let e = dia
+			.attached(0)
+			.find(|i| dia.edges[*i].to == Some(2) || dia.edges[*i].from == Some(2))
+			.unwrap();
+		let (from, to) = dia.ports(e);
+		let sides: Vec<_> = [from, to]
+			.into_iter()
+			.flatten()
+			.map(|p| (p.id, p.side))
+			.collect();
+		assert!(sides == vec![(0, Dn), (2, Up)] || sides == vec![(2, Up), (0, Dn)]);
+
+		// the dangling edge off the bottom box has just the one port
+		let e = dia
+			.attached(2)
+			.find(|i| dia.edges[*i].to.is_none())
+			.unwrap();
+		assert_eq!(
+			(
+				Some(Port {
+					id: 2,
+					side: Rt,
+					at: Point { row: 8, col: 4 }
+				}),
+				None
+			),
+			dia.ports(e)
+		);
+	}
+}
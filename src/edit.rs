@@ -0,0 +1,760 @@
+use crate::diagram::{Diagram, EdgeId, Port};
+use crate::parse::*;
+
+// a is inside b without touching b's border
+fn inside(a: TBox, b: TBox) -> bool {
+	a.0.row > b.0.row && a.0.col > b.0.col && a.1.row < b.1.row && a.1.col < b.1.col
+}
+
+fn shift(b: TBox, d: Direction) -> Option<TBox> {
+	let (dr, dc) = d.delta();
+	Some(TBox(b.0.offset(dr, dc)?, b.1.offset(dr, dc)?))
+}
+
+// can b be placed at nb without running into any box other than the ones it
+// contains (which move with it) or the one containing it (which it must stay in)
+fn fits(bs: &[TBox], b: TBox, nb: TBox) -> bool {
+	bs.iter().filter(|o| **o != b && !inside(**o, b)).all(|o| {
+		if inside(b, *o) {
+			inside(nb, *o)
+		} else {
+			!o.intersects(nb)
+		}
+	})
+}
+
+// move the first point of a path one step in d, dragging the next corner
+// along or putting a jog in the path so every segment stays straight
+//
+//  |   ,--      |   ,--       |     |      |     |
+//  |---'    =>  |   |    ,    |-----|  =>  |  ,--|
+//  |            |---'         |     |      |--'  |
+//
+fn drag(mut pth: Vec<Point>, d: Direction) -> Option<Vec<Point>> {
+	if pth.len() < 2 {
+		return None;
+	}
+	let p0 = pth[0].in_dir(d)?;
+	let seg = pth[0].dir_to(pth[1])?;
+	if seg == d || seg == d.rev() {
+		if p0 == pth[1] {
+			return None;
+		}
+		pth[0] = p0;
+		return Some(pth);
+	}
+	if pth.len() == 2 {
+		let (a, z) = (pth[0], pth[1]);
+		let (c1, c2) = match seg {
+			Direction::Lt | Direction::Rt => {
+				if a.col.abs_diff(z.col) < 2 {
+					return None;
+				}
+				let m = (a.col + z.col) / 2;
+				(Point::from((p0.row, m)), Point::from((z.row, m)))
+			}
+			Direction::Up | Direction::Dn => {
+				if a.row.abs_diff(z.row) < 2 {
+					return None;
+				}
+				let m = (a.row + z.row) / 2;
+				(Point::from((m, p0.col)), Point::from((m, z.col)))
+			}
+		};
+		return Some(vec![p0, c1, c2, z]);
+	}
+	let p1 = pth[1].in_dir(d)?;
+	let next = pth[1].dir_to(pth[2])?;
+	if (next != d && next != d.rev()) || p1 == pth[2] {
+		return None;
+	}
+	pth[0] = p0;
+	pth[1] = p1;
+	Some(pth)
+}
+
+pub fn draw_path(lines: &mut Lines, pth: &[Point], ends: (char, char)) {
+	let cells = path_points(pth);
+	for w in cells.windows(2) {
+		if let Some(d) = w[0].dir_to(w[1]) {
+			lines.set(w[1], corner(d, d));
+		}
+	}
+	for w in pth.windows(3) {
+		if let (Some(a), Some(b)) = (w[1].dir_to(w[0]), w[1].dir_to(w[2])) {
+			lines.set(w[1], corner(a, b));
+		}
+	}
+	if let (Some(fst), Some(lst)) = (pth.first(), pth.last()) {
+		lines.set(*lst, ends.1);
+		lines.set(*fst, ends.0);
+	}
+}
+
+// blank out a path, leaving any cell that another path still needs
+pub fn erase_path(lines: &mut Lines, pth: &[Point], keep: &[&Vec<Point>]) {
+	for p in path_points(pth) {
+		if lines.at(p).is_some() && !keep.iter().any(|e| path_contains(e, p)) {
+			lines.set(p, ' ');
+		}
+	}
+}
+
+// blank out box id along with the edges in drop, leaving any cell that one of
+// the other edges still runs through or another box's border is drawn on. a
+// box with others inside it is left alone, since they and their edges would
+// be stranded
+//
+// with nothing else inside, the box is just its border, its label and
+// whatever edges cross it, so the rest of it can go
+pub fn delete_box(lines: &mut Lines, dia: &Diagram, id: BoxId, drop: &[EdgeId]) -> Option<()> {
+	if dia.children(id).next().is_some() {
+		return None;
+	}
+	let b = dia.boxes[id];
+	let keep: Vec<_> = (0..dia.edges.len())
+		.filter(|i| !drop.contains(i))
+		.map(|i| &dia.edges[i].path)
+		.collect();
+	let kept = |p: Point| {
+		keep.iter().any(|e| path_contains(e, p))
+			|| dia.boxes.iter().any(|o| *o != b && o.on_border(p))
+	};
+	let gone: Vec<Point> = b
+		.points()
+		.chain(drop.iter().flat_map(|i| path_points(&dia.edges[*i].path)))
+		.filter(|p| lines.at(*p).is_some() && !kept(*p))
+		.collect();
+	for p in gone {
+		lines.set(p, ' ');
+	}
+	Some(())
+}
+
+// which side of box id a port is on, if it's on that box at all
+fn side_of(id: BoxId, port: Option<Port>) -> Option<Direction> {
+	port.filter(|p| p.id == id).map(|p| p.side)
+}
+
+// move box id (and everything inside it) one cell in direction d, stretching
+// or bending every edge attached to it, returns where the box ended up. it
+// won't move onto anything but its own edges, or while an edge runs out
+// through its border, since only the inside half of that would come along
+pub fn translate(lines: &mut Lines, dia: &Diagram, id: BoxId, d: Direction) -> Option<TBox> {
+	let b = dia.boxes[id];
+	let nb = shift(b, d)?;
+	if !fits(&dia.boxes, b, nb) || dia.entering(id).next().is_some() {
+		return None;
+	}
+
+	let mut keep = vec![];
+	let mut moved = vec![];
+	for (i, e) in dia.edges.iter().map(|e| &e.path).enumerate() {
+		let (fst, lst) = (e[0], e[e.len() - 1]);
+		let ends = (lines[fst], lines[lst]);
+		let (from, to) = dia.ports(i);
+		match (side_of(id, from), side_of(id, to)) {
+			(None, None) => keep.push(e),
+			// a single cell squeezed between two boxes can only grow or stay put
+			(Some(out), _) | (_, Some(out)) if e.len() == 1 && from.is_some() && to.is_some() => {
+				if d != out.rev() {
+					return None;
+				}
+				moved.push((e, vec![fst.in_dir(d)?, fst], ends));
+			}
+			(Some(_), Some(_)) => {
+				let pth = e.iter().map(|p| p.in_dir(d)).collect::<Option<Vec<_>>>()?;
+				moved.push((e, pth, ends));
+			}
+			(Some(_), None) => moved.push((e, drag(e.clone(), d)?, ends)),
+			(None, Some(_)) => {
+				let mut pth = drag(e.iter().rev().copied().collect(), d)?;
+				pth.reverse();
+				moved.push((e, pth, ends));
+			}
+		}
+	}
+	let ours = |p: Point| moved.iter().any(|(e, _, _)| path_contains(e, p));
+	let taken = |p: Point| lines.at(p).is_some_and(|c| c != ' ') && !ours(p);
+	if nb.points().any(|p| !b.contains(p) && taken(p)) {
+		return None;
+	}
+
+	let snapshot: Vec<(Point, char)> = b.points().map(|p| (p, lines[p])).collect();
+	for (e, _, _) in &moved {
+		erase_path(lines, e, &keep);
+	}
+	for (p, _) in &snapshot {
+		lines.set(*p, ' ');
+	}
+	for (p, c) in snapshot {
+		lines.set(p.in_dir(d)?, c);
+	}
+	for (_, pth, ends) in &moved {
+		draw_path(lines, pth, *ends);
+	}
+	Some(nb)
+}
+
+// translate b one step in d, first shoving along whatever box is in the way
+fn push(lines: &mut Lines, b: TBox, d: Direction) -> Option<TBox> {
+	let nb = shift(b, d)?;
+	while let Some(o) = boxes(lines)
+		.into_iter()
+		.find(|o| *o != b && !inside(*o, b) && !inside(b, *o) && o.intersects(nb))
+	{
+		push(lines, o, d)?;
+	}
+	let dia = Diagram::parse(lines);
+	translate(lines, &dia, dia.boxes.iter().position(|o| *o == b)?, d)
+}
+
+// move the side of b facing d one cell outwards (grow) or inwards, shoving
+// aside only the boxes the bigger box would run into
+//
+//  ,--. ,--.   grow Dn   ,--. ,--.
+//  |  | |  |   ------>   |  | |  |
+//  '--' '--'             |  | '--'
+//  ,--.                  '--'
+//  '--'                  ,--.
+//                        '--'
+pub fn resize(lines: &mut Lines, b: TBox, side: Direction, grow: bool) -> Option<TBox> {
+	let orig = lines.clone();
+	let ret = resize_side(lines, b, side, grow);
+	if ret.is_none() {
+		*lines = orig;
+	}
+	ret
+}
+
+fn resize_side(lines: &mut Lines, b: TBox, side: Direction, grow: bool) -> Option<TBox> {
+	use Direction::*;
+	let d = if grow { side } else { side.rev() };
+	let (dr, dc) = d.delta();
+	let nb = match side {
+		Up | Lt => TBox(b.0.offset(dr, dc)?, b.1),
+		Dn | Rt => TBox(b.0, b.1.offset(dr, dc)?),
+	};
+	// keep at least a corner on each end of every side
+	if nb.0.row >= nb.1.row || nb.0.col >= nb.1.col {
+		return None;
+	}
+
+	let old = border_in_dir(b, side);
+	let gap = if grow { old.in_dir(d)? } else { old };
+	if grow {
+		while let Some(o) = boxes(lines)
+			.into_iter()
+			.find(|o| *o != b && !inside(*o, b) && !inside(b, *o) && o.intersects(gap))
+		{
+			push(lines, o, d)?;
+		}
+	} else {
+		let inner = TBox(
+			old.in_dir(d)?.0.offset(dc.abs(), dr.abs())?,
+			old.in_dir(d)?.1.offset(-dc.abs(), -dr.abs())?,
+		);
+		if inner.points().any(|p| lines.at(p).unwrap_or(' ') != ' ') {
+			return None;
+		}
+	}
+	let dia = Diagram::parse(lines);
+	if dia.boxes.iter().any(|o| inside(b, *o) && !inside(nb, *o)) {
+		return None;
+	}
+
+	let id = dia.boxes.iter().position(|o| *o == b)?;
+	let nbord = border(nb);
+	let mut keep = vec![];
+	let mut moved = vec![];
+	for (i, e) in dia.edges.iter().map(|e| &e.path).enumerate() {
+		let (fst, lst) = (e[0], e[e.len() - 1]);
+		let ends = (lines[fst], lines[lst]);
+		let (from, to) = dia.ports(i);
+		match (side_of(id, from), side_of(id, to)) {
+			(Some(s), _) | (_, Some(s)) if s == side && e.len() == 1 => {
+				if grow {
+					return None;
+				}
+				moved.push((e, vec![fst.in_dir(d)?, fst], ends));
+			}
+			(Some(s), _) if s == side => moved.push((e, drag(e.clone(), d)?, ends)),
+			(_, Some(s)) if s == side => {
+				let mut pth = drag(e.iter().rev().copied().collect(), d)?;
+				pth.reverse();
+				moved.push((e, pth, ends));
+			}
+			// attached to a side that got shorter, and now hangs off the end
+			(Some(_), _) if !nbord.iter().any(|(q, _)| *q == fst) => return None,
+			(_, Some(_)) if !nbord.iter().any(|(q, _)| *q == lst) => return None,
+			_ => keep.push(e),
+		}
+	}
+	for (e, _, _) in &moved {
+		erase_path(lines, e, &keep);
+	}
+	if grow && gap.points().any(|p| lines.at(p).unwrap_or(' ') != ' ') {
+		return None;
+	}
+
+	// the old corners become part of the sides that got longer, drawn with
+	// whatever the rest of those sides are (so a unicode box stays unicode)
+	let along = |p: Point| match p.in_dir(side.rev()).and_then(|q| lines.at(q)) {
+		Some(c) if can_go(c, side) && can_go(c, side.rev()) => c,
+		_ => match side {
+			Up | Dn => corner(Up, Dn),
+			Lt | Rt => corner(Lt, Rt),
+		},
+	};
+	let ends = [(old.0, along(old.0)), (old.1, along(old.1))];
+	for p in old.points() {
+		let q = p.in_dir(d)?;
+		lines.set(q, lines[p]);
+		let fill = match ends.iter().find(|(e, _)| *e == p) {
+			Some((_, c)) if grow => *c,
+			_ => ' ',
+		};
+		lines.set(p, fill);
+	}
+	for (_, pth, ends) in &moved {
+		draw_path(lines, pth, *ends);
+	}
+	Some(nb)
+}
+
+pub fn draw_box(lines: &mut Lines, b: TBox) {
+	use Direction::*;
+	for d in [Up, Dn] {
+		for p in border_in_dir(b, d).points() {
+			lines.set(p, corner(Lt, Rt));
+		}
+	}
+	for d in [Lt, Rt] {
+		for p in border_in_dir(b, d).points() {
+			lines.set(p, corner(Up, Dn));
+		}
+	}
+	lines.set(b.0, corner(Dn, Rt));
+	lines.set(Point::from((b.0.row, b.1.col)), corner(Dn, Lt));
+	lines.set(Point::from((b.1.row, b.0.col)), corner(Up, Rt));
+	lines.set(b.1, corner(Up, Lt));
+}
+
+// put a new rows x cols box with its top left at p, shoving the boxes in its
+// way (and the edges attached to them) right (d = Rt) or down (d = Dn) until
+// there's a blank column or row between them and it. anything else where it
+// would go, like text or a line running across, stays put and stops it
+//
+//  ,---. ,---.        ,---. ,--. ,---.
+//  |   | *   |   Rt   |   | |  | |   |
+//  '---' '---'  --->  '---' '--' '---'
+//
+pub fn insert(
+	lines: &mut Lines,
+	p: Point,
+	(rows, cols): (usize, usize),
+	d: Direction,
+) -> Option<TBox> {
+	let orig = lines.clone();
+	let ret = make_room(lines, p, (rows, cols), d);
+	if ret.is_none() {
+		*lines = orig;
+	}
+	ret
+}
+
+fn make_room(
+	lines: &mut Lines,
+	p: Point,
+	(rows, cols): (usize, usize),
+	d: Direction,
+) -> Option<TBox> {
+	let b = TBox(p, p.offset(rows as isize - 1, cols as isize - 1)?);
+	// the box and the blank column or row after it
+	let room = match d {
+		Direction::Rt => TBox(b.0, b.1.offset(0, 1)?),
+		Direction::Dn => TBox(b.0, b.1.offset(1, 0)?),
+		_ => return None,
+	};
+	let before = |o: TBox| match d {
+		Direction::Rt => o.0.col < b.0.col,
+		_ => o.0.row < b.0.row,
+	};
+	// a box it's going inside of stays where it is
+	while let Some(o) = boxes(lines)
+		.into_iter()
+		.find(|o| !inside(room, *o) && o.intersects(room))
+	{
+		// and one it would land halfway into can't be pushed past it
+		if before(o) {
+			return None;
+		}
+		push(lines, o, d)?;
+	}
+	if room.points().any(|p| lines.at(p).unwrap_or(' ') != ' ') {
+		return None;
+	}
+	draw_box(lines, b);
+	Some(b)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn lines(s: &str) -> Lines {
+		Lines(s.lines().map(|l| l.chars().collect()).collect())
+	}
+
+	fn text(lines: &Lines) -> String {
+		lines
+			.0
+			.iter()
+			.map(|l| l.iter().collect::<String>().trim_end().to_string())
+			.collect::<Vec<_>>()
+			.join("\n")
+	}
+
+	#[test]
+	fn test_translate() {
+		let mut ls = lines(
+			r#"
+ ,--.    ,--.
+ |  |----|  |
+ '--'    '--'
+"#,
+		);
+		let dia = Diagram::parse(&ls);
+		let b = translate(&mut ls, &dia, 0, Direction::Dn);
+		assert_eq!(
+			Some(TBox(Point { row: 2, col: 1 }, Point { row: 4, col: 4 })),
+			b
+		);
+		assert_eq!(
+			r#"
+         ,--.
+ ,--. ,--|  |
+ |  |-'  '--'
+ '--'"#,
+			text(&ls)
+		);
+		// still two boxes joined by one edge
+		let bs = boxes(&ls);
+		let es = edges(&ls, &bs);
+		assert_eq!(2, bs.len());
+		assert_eq!(1, es.len());
+
+		let dia = Diagram::parse(&ls);
+		let b = translate(&mut ls, &dia, 0, Direction::Lt);
+		assert_eq!(
+			Some(TBox(Point { row: 1, col: 8 }, Point { row: 3, col: 11 })),
+			b
+		);
+		assert_eq!(
+			r#"
+        ,--.
+ ,--. ,-|  |
+ |  |-' '--'
+ '--'"#,
+			text(&ls)
+		);
+	}
+
+	#[test]
+	fn test_insert() {
+		let mut ls = lines(
+			r#"
+ ,---. ,---.
+ | a | | b |
+ '---' '---'
+   |     |
+   '-----'
+Some prose about the diagram
+"#,
+		);
+		// right on top of a, or over the line joining them
+		assert_eq!(
+			None,
+			insert(&mut ls, Point { row: 1, col: 3 }, (3, 4), Direction::Rt)
+		);
+		assert_eq!(
+			None,
+			insert(&mut ls, Point { row: 3, col: 2 }, (3, 4), Direction::Rt)
+		);
+		let b = insert(&mut ls, Point { row: 1, col: 7 }, (3, 4), Direction::Rt);
+		assert_eq!(
+			Some(TBox(Point { row: 1, col: 7 }, Point { row: 3, col: 10 })),
+			b
+		);
+		// b and its end of the edge move over, the prose stays as it was
+		assert_eq!(
+			r#"
+ ,---. ,--. ,---.
+ | a | |  | | b |
+ '---' '--' '---'
+   |          |
+   '----------'
+Some prose about the diagram"#,
+			text(&ls)
+		);
+		let bs = boxes(&ls);
+		assert_eq!(3, bs.len());
+		assert_eq!(1, edges(&ls, &bs).len());
+	}
+
+	#[test]
+	fn test_insert_below() {
+		let mut ls = lines(
+			r#"
+ ,---.   prose to the right
+ | a |
+ '---'
+   |
+ ,---.
+ | b |
+ '---'
+"#,
+		);
+		let b = insert(&mut ls, Point { row: 4, col: 5 }, (2, 2), Direction::Dn);
+		assert_eq!(
+			Some(TBox(Point { row: 4, col: 5 }, Point { row: 5, col: 6 })),
+			b
+		);
+		assert_eq!(
+			r#"
+ ,---.   prose to the right
+ | a |
+ '---'
+   | ,.
+   | ''
+   |
+ ,---.
+ | b |
+ '---'"#,
+			text(&ls)
+		);
+		let bs = boxes(&ls);
+		assert_eq!(3, bs.len());
+		assert_eq!(1, edges(&ls, &bs).len());
+	}
+
+	#[test]
+	fn test_resize() {
+		let mut ls = lines(
+			r#"
+ ,--. ,--.
+ |  |-|  |
+ '--' '--'
+ ,--.   |
+ '--'   '->
+"#,
+		);
+		let bs = boxes(&ls);
+		let b = resize(&mut ls, bs[0], Direction::Dn, true);
+		assert_eq!(
+			Some(TBox(Point { row: 1, col: 1 }, Point { row: 4, col: 4 })),
+			b
+		);
+		assert_eq!(
+			r#"
+ ,--. ,--.
+ |  |-|  |
+ |  | '--'
+ '--'   |
+ ,--.   '->
+ '--'"#,
+			text(&ls)
+		);
+
+		let b = resize(&mut ls, b.unwrap(), Direction::Rt, true);
+		assert_eq!(None, b);
+		let b = resize(&mut ls, bs[1], Direction::Lt, false);
+		assert_eq!(
+			Some(TBox(Point { row: 1, col: 7 }, Point { row: 3, col: 9 })),
+			b
+		);
+		assert_eq!(
+			r#"
+ ,--.  ,-.
+ |  |--| |
+ |  |  '-'
+ '--'   |
+ ,--.   '->
+ '--'"#,
+			text(&ls)
+		);
+		let b = resize(&mut ls, b.unwrap(), Direction::Lt, false);
+		assert_eq!(
+			Some(TBox(Point { row: 1, col: 8 }, Point { row: 3, col: 9 })),
+			b
+		);
+		// too thin to shrink any further
+		assert_eq!(None, resize(&mut ls, b.unwrap(), Direction::Lt, false));
+		assert_eq!(None, resize(&mut ls, b.unwrap(), Direction::Rt, false));
+		assert_eq!(3, boxes(&ls).len());
+		assert_eq!(2, edges(&ls, &boxes(&ls)).len());
+	}
+
+	#[test]
+	fn test_resize_unicode() {
+		let mut ls = lines(
+			r#"
+ ┌──┐
+ │  │
+ └──┘
+"#,
+		);
+		let b = boxes(&ls)[0];
+		let b = resize(&mut ls, b, Direction::Dn, true).unwrap();
+		let b = resize(&mut ls, b, Direction::Rt, true).unwrap();
+		resize(&mut ls, b, Direction::Up, true).unwrap();
+		assert_eq!(
+			r#" ┌───┐
+ │   │
+ │   │
+ │   │
+ └───┘"#,
+			text(&ls)
+		);
+		assert_eq!(1, boxes(&ls).len());
+	}
+
+	#[test]
+	fn test_translate_blocked() {
+		let mut ls = lines(
+			r#"
+ ,--.
+ |  |
+ '--'
+ ,--.
+ '--'
+"#,
+		);
+		let dia = Diagram::parse(&ls);
+		assert_eq!(2, dia.boxes.len());
+		assert_eq!(None, translate(&mut ls, &dia, 0, Direction::Dn));
+		assert!(translate(&mut ls, &dia, 0, Direction::Rt).is_some());
+	}
+
+	#[test]
+	fn test_translate_onto_text() {
+		let mut ls = lines(
+			r#"
+ ,--. hello
+ |  | world
+ '--'
+
+ -------
+"#,
+		);
+		// a cell's room either way, but no more: the text to the right and the
+		// line below aren't the box's to move
+		for d in [Direction::Rt, Direction::Dn] {
+			let dia = Diagram::parse(&ls);
+			assert!(translate(&mut ls, &dia, 0, d).is_some());
+			let dia = Diagram::parse(&ls);
+			assert_eq!(None, translate(&mut ls, &dia, 0, d));
+		}
+		assert_eq!(
+			r#"
+      hello
+  ,--.world
+  |  |
+  '--'
+ -------"#,
+			text(&ls)
+		);
+	}
+
+	#[test]
+	fn test_translate_crossed() {
+		let mut ls = lines(
+			r#"
+ ,---------.
+ | ,---.   |
+ | | a |---|--->,---.
+ | '---'   |    | b |
+ '---------'    '---'
+
+"#,
+		);
+		let before = text(&ls);
+		let dia = Diagram::parse(&ls);
+		assert_eq!(vec![0], dia.entering(0).collect::<Vec<_>>());
+		// the line out of the container would be split in two
+		for d in Direction::VALUES {
+			assert_eq!(None, translate(&mut ls, &dia, 0, d));
+		}
+		assert_eq!(before, text(&ls));
+	}
+
+	#[test]
+	fn test_delete_box() {
+		let mut ls = lines(
+			r#"
+ ,---.
+ | a |
+ '---'
+   |
+ ,-'----.
+ v      v
+,---. ,---.
+| b | | c |
+'---' '---'
+"#,
+		);
+		let dia = Diagram::parse(&ls);
+		// a to c and b to c, but not a to b
+		let gone: Vec<EdgeId> = dia.attached(2).collect();
+		assert_eq!(2, gone.len());
+		delete_box(&mut ls, &dia, 2, &gone).unwrap();
+		assert_eq!(
+			r#"
+ ,---.
+ | a |
+ '---'
+   |
+ ,-'
+ v
+,---.
+| b |
+'---'"#,
+			text(&ls)
+		);
+		let bs = boxes(&ls);
+		let es = edges(&ls, &bs);
+		assert_eq!(2, bs.len());
+		assert_eq!(1, es.len());
+	}
+
+	#[test]
+	fn test_delete_nested() {
+		let mut ls = lines(
+			r#"
+ ,---------.
+ | ,---.   |
+ | | a |---|--->,---.
+ | '---'   |    | b |
+ '---------'    '---'
+"#,
+		);
+		let dia = Diagram::parse(&ls);
+		assert_eq!(1, dia.edges.len());
+		// the container would strand a
+		assert_eq!(None, delete_box(&mut ls, &dia, 0, &[]));
+		// a goes with its edge, and the container keeps its border
+		delete_box(&mut ls, &dia, 1, &[0]).unwrap();
+		assert_eq!(
+			r#"
+ ,---------.
+ |         |
+ |         |    ,---.
+ |         |    | b |
+ '---------'    '---'"#,
+			text(&ls)
+		);
+		let bs = boxes(&ls);
+		assert_eq!(2, bs.len());
+		assert!(edges(&ls, &bs).is_empty());
+	}
+}
@@ -0,0 +1,728 @@
+use crate::diagram::Diagram;
+use crate::parse::*;
+use std::io::{self, Write};
+
+// a double quoted dot string
+fn quote(s: &str) -> String {
+	let mut ret = String::from("\"");
+	for c in s.chars() {
+		match c {
+			'"' | '\\' => {
+				ret.push('\\');
+				ret.push(c);
+			}
+			'\n' => ret.push_str("\\n"),
+			c => ret.push(c),
+		}
+	}
+	ret.push('"');
+	ret
+}
+
+fn is_cluster(dia: &Diagram, id: BoxId) -> bool {
+	dia.children(id).next().is_some()
+}
+
+// a box with boxes inside it becomes a cluster, with an invisible node for
+// edges to the box itself to attach to
+fn dot_box(dia: &Diagram, id: BoxId, depth: usize, w: &mut impl Write) -> io::Result<()> {
+	let ind = "\t".repeat(depth);
+	let label = quote(&dia.name(id));
+	if !is_cluster(dia, id) {
+		return writeln!(w, "{}n{} [label={}];", ind, id, label);
+	}
+	writeln!(w, "{}subgraph cluster_{} {{", ind, id)?;
+	writeln!(w, "{}\tlabel={};", ind, label)?;
+	writeln!(w, "{}\tn{} [shape=point, style=invis];", ind, id)?;
+	for c in dia.children(id) {
+		dot_box(dia, c, depth + 1, w)?;
+	}
+	writeln!(w, "{}}}", ind)
+}
+
+//  ,---.      ,---.         digraph {
+//  | a |----->| b |  --->      n0 [label="a"];
+//  '---'      '---'            n1 [label="b"];
+//                              n0 -> n1;
+//                           }
+pub fn dot(dia: &Diagram, mut w: impl Write) -> io::Result<()> {
+	writeln!(w, "digraph {{")?;
+	writeln!(w, "\tcompound=true;")?;
+	writeln!(w, "\tnode [shape=box];")?;
+	for id in dia.roots() {
+		dot_box(dia, id, 1, &mut w)?;
+	}
+	for (i, e) in dia.edges.iter().enumerate() {
+		let mut attrs = vec![];
+		let mut end = |id: Option<BoxId>, which: &str, w: &mut dyn Write| -> io::Result<String> {
+			match id {
+				Some(id) => {
+					if is_cluster(dia, id) {
+						let side = if which == "head" { "lhead" } else { "ltail" };
+						attrs.push(format!("{}=cluster_{}", side, id));
+					}
+					Ok(format!("n{}", id))
+				}
+				// a dangling end still needs somewhere to point
+				None => {
+					let n = format!("e{}_{}", i, which);
+					writeln!(w, "\t{} [shape=point];", n)?;
+					Ok(n)
+				}
+			}
+		};
+		let from = end(e.from, "tail", &mut w)?;
+		let to = end(e.to, "head", &mut w)?;
+		match (&e.head, &e.tail) {
+			(Some(_), Some(_)) => attrs.push("dir=both".to_string()),
+			(None, None) => attrs.push("dir=none".to_string()),
+			(None, Some(_)) => attrs.push("dir=back".to_string()),
+			(Some(_), None) => {}
+		}
+		if let Some(l) = &e.label {
+			attrs.push(format!("label={}", quote(&l.text.text())));
+		}
+		if attrs.is_empty() {
+			writeln!(w, "\t{} -> {};", from, to)?;
+		} else {
+			writeln!(w, "\t{} -> {} [{}];", from, to, attrs.join(", "))?;
+		}
+	}
+	writeln!(w, "}}")
+}
+
+// a mermaid node label, which can't escape quotes with a backslash
+fn mermaid_quote(s: &str) -> String {
+	let s = s.replace('"', "#quot;").replace('\n', "<br>");
+	format!("\"{}\"", s)
+}
+
+// lay the chart out the way most of the edges already run in the drawing
+fn mermaid_dir(dia: &Diagram) -> &'static str {
+	let centre = |id: BoxId| {
+		let TBox(tl, br) = dia.boxes[id];
+		((tl.row + br.row) as isize, (tl.col + br.col) as isize)
+	};
+	let (mut across, mut down) = (0, 0);
+	for e in &dia.edges {
+		if let (Some(from), Some(to)) = (e.from, e.to) {
+			let ((r0, c0), (r1, c1)) = (centre(from), centre(to));
+			across += (c1 - c0).abs();
+			// rows are about twice as tall as columns are wide
+			down += 2 * (r1 - r0).abs();
+		}
+	}
+	if down > across {
+		"TD"
+	} else {
+		"LR"
+	}
+}
+
+// unlike dot, mermaid lets edges point at a subgraph directly
+fn mermaid_box(dia: &Diagram, id: BoxId, depth: usize, w: &mut impl Write) -> io::Result<()> {
+	let ind = "    ".repeat(depth);
+	let label = mermaid_quote(&dia.labels[id].text());
+	if !is_cluster(dia, id) {
+		return writeln!(w, "{}n{}[{}]", ind, id, label);
+	}
+	writeln!(w, "{}subgraph n{} [{}]", ind, id, label)?;
+	for c in dia.children(id) {
+		mermaid_box(dia, c, depth + 1, w)?;
+	}
+	writeln!(w, "{}end", ind)
+}
+
+//  ,---.      ,---.         flowchart LR
+//  | a |----->| b |  --->       n0["a"]
+//  '---'      '---'             n1["b"]
+//                               n0 --> n1
+pub fn mermaid(dia: &Diagram, mut w: impl Write) -> io::Result<()> {
+	writeln!(w, "flowchart {}", mermaid_dir(dia))?;
+	for id in dia.roots() {
+		mermaid_box(dia, id, 1, &mut w)?;
+	}
+	for (i, e) in dia.edges.iter().enumerate() {
+		let mut end = |id: Option<BoxId>, which: &str| -> io::Result<String> {
+			match id {
+				Some(id) => Ok(format!("n{}", id)),
+				None => {
+					let n = format!("e{}_{}", i, which);
+					writeln!(w, "    {}(( ))", n)?;
+					Ok(n)
+				}
+			}
+		};
+		let mut from = end(e.from, "tail")?;
+		let mut to = end(e.to, "head")?;
+		let arrow = match (&e.head, &e.tail) {
+			(Some(_), Some(_)) => "<-->",
+			(None, None) => "---",
+			(Some(_), None) => "-->",
+			// mermaid has no <--, so turn the edge around
+			(None, Some(_)) => {
+				std::mem::swap(&mut from, &mut to);
+				"-->"
+			}
+		};
+		match &e.label {
+			Some(l) => {
+				let l = l.text.text().replace('|', "#124;");
+				writeln!(w, "    {} {}|{}| {}", from, arrow, mermaid_quote(&l), to)?;
+			}
+			None => writeln!(w, "    {} {} {}", from, arrow, to)?,
+		}
+	}
+	Ok(())
+}
+
+// how big a character cell is in the svg, and what to draw text with
+pub struct SvgStyle {
+	pub cell: (f32, f32),
+	pub font: String,
+	pub font_size: f32,
+}
+
+impl Default for SvgStyle {
+	fn default() -> SvgStyle {
+		SvgStyle {
+			cell: (10.0, 20.0),
+			font: "monospace".to_string(),
+			font_size: 16.0,
+		}
+	}
+}
+
+fn xml_escape(s: &str) -> String {
+	s.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+}
+
+// the far side of an edge's end cell, either the box border it meets or half
+// a cell on past a dangling end
+fn svg_end(
+	dia: &Diagram,
+	path: &[Point],
+	id: Option<BoxId>,
+	arrow: Option<Arrow>,
+	st: &SvgStyle,
+) -> (f32, f32) {
+	let p = path[0];
+	let (cw, ch) = st.cell;
+	let (x, y) = ((p.col as f32 + 0.5) * cw, (p.row as f32 + 0.5) * ch);
+	let out = match id {
+		Some(id) => Direction::VALUES
+			.into_iter()
+			.find(|d| p.in_dir(*d).is_some_and(|q| dia.boxes[id].on_border(q))),
+		None => path
+			.get(1)
+			.and_then(|q| q.dir_to(p))
+			.or(arrow.map(|a| a.dir)),
+	};
+	let scale = if id.is_some() { 1.0 } else { 0.5 };
+	match out.map(Direction::delta) {
+		Some((dr, dc)) => (x + dc as f32 * cw * scale, y + dr as f32 * ch * scale),
+		None => (x, y),
+	}
+}
+
+//  ,---.      ,---.         <rect .../>  <rect .../>
+//  | a |----->| b |  --->   <polyline ... marker-end="url(#head)"/>
+//  '---'      '---'         <text ...>a</text>  <text ...>b</text>
+pub fn svg(dia: &Diagram, lines: &Lines, st: &SvgStyle, mut w: impl Write) -> io::Result<()> {
+	let (cw, ch) = st.cell;
+	let cols = lines.0.iter().map(Vec::len).max().unwrap_or(0);
+	writeln!(
+		w,
+		r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}">"#,
+		cols as f32 * cw,
+		lines.0.len() as f32 * ch
+	)?;
+	writeln!(w, "<defs>")?;
+	writeln!(
+		w,
+		r#"<marker id="head" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="{}" markerHeight="{}" markerUnits="userSpaceOnUse" orient="auto-start-reverse"><path d="M0,0 L10,5 L0,10 z"/></marker>"#,
+		cw, cw
+	)?;
+	writeln!(w, "</defs>")?;
+	writeln!(w, r#"<g fill="none" stroke="black">"#)?;
+	for b in &dia.boxes {
+		writeln!(
+			w,
+			r#"<rect x="{}" y="{}" width="{}" height="{}"/>"#,
+			(b.0.col as f32 + 0.5) * cw,
+			(b.0.row as f32 + 0.5) * ch,
+			(b.1.col - b.0.col) as f32 * cw,
+			(b.1.row - b.0.row) as f32 * ch
+		)?;
+	}
+	for e in &dia.edges {
+		let mut pts = vec![svg_end(dia, &e.path, e.from, e.tail, st)];
+		pts.extend(
+			e.path
+				.iter()
+				.map(|p| ((p.col as f32 + 0.5) * cw, (p.row as f32 + 0.5) * ch)),
+		);
+		let rev: Vec<Point> = e.path.iter().rev().copied().collect();
+		pts.push(svg_end(dia, &rev, e.to, e.head, st));
+		let pts: Vec<String> = pts.iter().map(|(x, y)| format!("{},{}", x, y)).collect();
+		write!(w, r#"<polyline points="{}""#, pts.join(" "))?;
+		if e.tail.is_some() {
+			write!(w, r#" marker-start="url(#head)""#)?;
+		}
+		if e.head.is_some() {
+			write!(w, r#" marker-end="url(#head)""#)?;
+		}
+		writeln!(w, "/>")?;
+	}
+	writeln!(w, "</g>")?;
+
+	// whatever isn't a box or an edge, a word at a time so each one lands on
+	// its own cells whatever the font
+	writeln!(
+		w,
+		r#"<g font-family="{}" font-size="{}" dominant-baseline="central">"#,
+		xml_escape(&st.font),
+		st.font_size
+	)?;
+	let drawn = |p: Point| {
+		dia.boxes.iter().any(|b| b.on_border(p))
+			|| dia.edges.iter().any(|e| path_contains(&e.path, p))
+	};
+	for (row, line) in lines.0.iter().enumerate() {
+		let mut col = 0;
+		while col < line.len() {
+			let start = col;
+			while col < line.len() && !line[col].is_whitespace() && !drawn(Point { row, col }) {
+				col += 1;
+			}
+			if col == start {
+				col += 1;
+				continue;
+			}
+			let word: String = line[start..col].iter().collect();
+			writeln!(
+				w,
+				r#"<text x="{}" y="{}" textLength="{}" lengthAdjust="spacingAndGlyphs">{}</text>"#,
+				start as f32 * cw,
+				(row as f32 + 0.5) * ch,
+				(col - start) as f32 * cw,
+				xml_escape(&word)
+			)?;
+		}
+	}
+	writeln!(w, "</g>")?;
+	writeln!(w, "</svg>")
+}
+
+// the same blue boxes and yellow edges simp_draw prints, hovering over an edge
+// lights up the whole of it
+const HTML_HEAD: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<style>
+pre.graphedit .box { color: #00a; }
+pre.graphedit .edge { color: #a80; }
+pre.graphedit .edge.hover { background: #fe8; }
+</style>
+</head>
+<body>
+<pre class="graphedit">"#;
+
+const HTML_TAIL: &str = r#"</pre>
+<script>
+for (const pre of document.querySelectorAll("pre.graphedit")) {
+	const light = (ev, on) => {
+		const ids = ev.target.dataset && ev.target.dataset.edge;
+		if (!ids) return;
+		for (const id of ids.split(" "))
+			for (const s of pre.querySelectorAll(`[data-edge~="${id}"]`))
+				s.classList.toggle("hover", on);
+	};
+	pre.addEventListener("mouseover", ev => light(ev, true));
+	pre.addEventListener("mouseout", ev => light(ev, false));
+}
+</script>
+</body>
+</html>
+"#;
+
+// what a cell is drawn as, and which box or edges it belongs to
+fn html_class(dia: &Diagram, p: Point, c: char) -> Option<(&'static str, &'static str, String)> {
+	let ids = |it: &mut dyn Iterator<Item = usize>| {
+		it.map(|i| i.to_string()).collect::<Vec<_>>().join(" ")
+	};
+	let edges = ids(&mut (0..dia.edges.len()).filter(|e| path_contains(&dia.edges[*e].path, p)));
+	if !edges.is_empty() {
+		return Some(("edge", "edge", edges));
+	}
+	if let Some(b) = dia.boxes.iter().position(|b| b.on_border(p)) {
+		return Some(("box", "box", b.to_string()));
+	}
+	if c.is_whitespace() {
+		return None;
+	}
+	// text in a box belongs to the innermost one
+	let inner = (0..dia.boxes.len())
+		.filter(|b| dia.boxes[*b].contains(p))
+		.min_by_key(|b| {
+			let TBox(tl, br) = dia.boxes[*b];
+			(br.row - tl.row) * (br.col - tl.col)
+		});
+	Some((
+		"text",
+		"box",
+		inner.map(|b| b.to_string()).unwrap_or_default(),
+	))
+}
+
+//  ,---.      ,---.         <span class="box" data-box="0">,---.</span>
+//  | a |----->| b |  --->   <span class="box" data-box="0">|</span> ...
+//  '---'      '---'         <span class="edge" data-edge="0">-----></span> ...
+pub fn html(dia: &Diagram, lines: &Lines, mut w: impl Write) -> io::Result<()> {
+	write!(w, "{}", HTML_HEAD)?;
+	for (row, line) in lines.0.iter().enumerate() {
+		let mut col = 0;
+		while col < line.len() {
+			let class = html_class(dia, Point { row, col }, line[col]);
+			let start = col;
+			col += 1;
+			while col < line.len() && html_class(dia, Point { row, col }, line[col]) == class {
+				col += 1;
+			}
+			let run: String = line[start..col].iter().collect();
+			match class {
+				None => write!(w, "{}", xml_escape(&run))?,
+				Some((class, _, ids)) if ids.is_empty() => {
+					write!(w, r#"<span class="{}">{}</span>"#, class, xml_escape(&run))?
+				}
+				Some((class, attr, ids)) => write!(
+					w,
+					r#"<span class="{}" data-{}="{}">{}</span>"#,
+					class,
+					attr,
+					ids,
+					xml_escape(&run)
+				)?,
+			}
+		}
+		writeln!(w)?;
+	}
+	write!(w, "{}", HTML_TAIL)
+}
+
+// a json string, escaping what json says has to be
+fn json_quote(s: &str) -> String {
+	let mut ret = String::from("\"");
+	for c in s.chars() {
+		match c {
+			'"' | '\\' => {
+				ret.push('\\');
+				ret.push(c);
+			}
+			'\n' => ret.push_str("\\n"),
+			c if (c as u32) < 0x20 => ret.push_str(&format!("\\u{:04x}", c as u32)),
+			c => ret.push(c),
+		}
+	}
+	ret.push('"');
+	ret
+}
+
+fn json_point(p: Point) -> String {
+	format!(r#"{{"row": {}, "col": {}}}"#, p.row, p.col)
+}
+
+fn json_id(id: Option<usize>) -> String {
+	id.map_or("null".to_string(), |i| i.to_string())
+}
+
+fn json_arrow(a: Option<Arrow>) -> String {
+	let dir = |d| match d {
+		Direction::Up => "up",
+		Direction::Dn => "down",
+		Direction::Lt => "left",
+		Direction::Rt => "right",
+	};
+	match a {
+		Some(a) => format!(
+			r#"{{"glyph": {}, "dir": "{}"}}"#,
+			json_quote(&a.glyph.to_string()),
+			dir(a.dir)
+		),
+		None => "null".to_string(),
+	}
+}
+
+fn json_lines(l: &[(Point, String)]) -> String {
+	let l: Vec<String> = l
+		.iter()
+		.map(|(p, s)| format!(r#"{{"at": {}, "text": {}}}"#, json_point(*p), json_quote(s)))
+		.collect();
+	format!("[{}]", l.join(", "))
+}
+
+// text that isn't in any box or on any edge, split into runs the same way
+// edge labels are
+fn free_text(dia: &Diagram, lines: &Lines) -> Vec<(Point, String)> {
+	text_runs(lines, &dia.boxes, |p| {
+		dia.edges.iter().any(|e| path_contains(&e.path, p))
+	})
+}
+
+// the schema, bumped whenever a field changes meaning or goes away (adding
+// fields doesn't bump it)
+//
+// {
+//   "version": 1,
+//   "boxes": [{
+//     "id": 0,                                 index, what edges refer to
+//     "tl": {"row": 1, "col": 1},              top left corner
+//     "br": {"row": 3, "col": 5},              bottom right corner
+//     "parent": null,                          id of the box it's inside
+//     "label": "a",                            its text, a line per row
+//     "lines": [{"at": {..}, "text": "a"}]     where each row of it starts
+//   }],
+//   "edges": [{
+//     "id": 0,
+//     "from": 0, "to": 1,                      box ids, null if dangling
+//     "points": [{"row": 2, "col": 6}, ..],    every cell, from end first
+//     "head": {"glyph": ">", "dir": "right"},  arrowhead at the to end, or null
+//     "tail": null,                            arrowhead at the from end
+//     "label": {"text": "go", "confidence": 1} or null
+//   }],
+//   "text": [{"at": {..}, "text": "note"}]     anything outside boxes and edges
+// }
+pub const JSON_VERSION: u32 = 1;
+
+pub fn json(dia: &Diagram, lines: &Lines, mut w: impl Write) -> io::Result<()> {
+	writeln!(w, "{{")?;
+	writeln!(w, "  \"version\": {},", JSON_VERSION)?;
+	writeln!(w, "  \"boxes\": [")?;
+	for (id, b) in dia.boxes.iter().enumerate() {
+		let sep = if id + 1 < dia.boxes.len() { "," } else { "" };
+		writeln!(
+			w,
+			r#"    {{"id": {}, "tl": {}, "br": {}, "parent": {}, "label": {}, "lines": {}}}{}"#,
+			id,
+			json_point(b.0),
+			json_point(b.1),
+			json_id(dia.parents[id]),
+			json_quote(&dia.labels[id].text()),
+			json_lines(&dia.labels[id].0),
+			sep
+		)?;
+	}
+	writeln!(w, "  ],")?;
+	writeln!(w, "  \"edges\": [")?;
+	for (id, e) in dia.edges.iter().enumerate() {
+		let sep = if id + 1 < dia.edges.len() { "," } else { "" };
+		let points: Vec<String> = path_points(&e.path).into_iter().map(json_point).collect();
+		let label = match &e.label {
+			Some(l) => format!(
+				r#"{{"text": {}, "confidence": {}}}"#,
+				json_quote(&l.text.text()),
+				l.confidence
+			),
+			None => "null".to_string(),
+		};
+		writeln!(
+			w,
+			r#"    {{"id": {}, "from": {}, "to": {}, "points": [{}], "head": {}, "tail": {}, "label": {}}}{}"#,
+			id,
+			json_id(e.from),
+			json_id(e.to),
+			points.join(", "),
+			json_arrow(e.head),
+			json_arrow(e.tail),
+			label,
+			sep
+		)?;
+	}
+	writeln!(w, "  ],")?;
+	writeln!(w, "  \"text\": {}", json_lines(&free_text(dia, lines)))?;
+	writeln!(w, "}}")
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn export(s: &str, f: impl Fn(&Diagram, &mut Vec<u8>) -> io::Result<()>) -> String {
+		let lines = Lines(s.lines().map(|l| l.chars().collect()).collect());
+		let mut out = vec![];
+		f(&Diagram::parse(&lines), &mut out).unwrap();
+		String::from_utf8(out).unwrap()
+	}
+
+	#[test]
+	fn test_dot() {
+		let s = r#"
+ ,-------------.
+ | outer ,---. |  go  ,---.
+ |       | a |-|----->| b |
+ |       '---' |      '---'
+ '-------------'<-->
+"#;
+		assert_eq!(
+			r#"digraph {
+	compound=true;
+	node [shape=box];
+	subgraph cluster_0 {
+		label="outer";
+		n0 [shape=point, style=invis];
+		n1 [label="a"];
+	}
+	n2 [label="b"];
+	e0_head [shape=point];
+	n0 -> e0_head [ltail=cluster_0, dir=both];
+	n1 -> n2 [label="go"];
+}
+"#,
+			export(s, |d, w| dot(d, w))
+		);
+
+		// named the same way as everywhere else, lines joined and a fallback
+		// for an empty box
+		let s = r#"
+ ,--. ,---.
+ |  | | x |
+ '--' | y |
+      '---'
+"#;
+		assert_eq!(
+			r#"digraph {
+	compound=true;
+	node [shape=box];
+	n0 [label="box0"];
+	n1 [label="x y"];
+}
+"#,
+			export(s, |d, w| dot(d, w))
+		);
+	}
+
+	#[test]
+	fn test_mermaid() {
+		let s = r#"
+ ,-------------.
+ | outer ,---. |  go  ,---.
+ |       | a |-|----->| b |
+ |       '---' |      '---'
+ '-------------'<-->
+"#;
+		assert_eq!(
+			r#"flowchart LR
+    subgraph n0 ["outer"]
+        n1["a"]
+    end
+    n2["b"]
+    e0_head(( ))
+    n0 <--> e0_head
+    n1 -->|"go"| n2
+"#,
+			export(s, |d, w| mermaid(d, w))
+		);
+
+		let s = r#"
+ ,---.
+ | a |
+ '---'
+   |
+   v
+ ,---.
+ | b |
+ '---'
+"#;
+		assert_eq!(
+			"flowchart TD\n    n0[\"a\"]\n    n1[\"b\"]\n    n0 --> n1\n",
+			export(s, |d, w| mermaid(d, w))
+		);
+	}
+
+	#[test]
+	fn test_svg() {
+		let s = r#"
+ ,-.  go  ,-.
+ |a|----->|b|
+ '-'      '-'
+"#;
+		let lines = Lines(s.lines().map(|l| l.chars().collect()).collect());
+		let mut out = vec![];
+		svg(
+			&Diagram::parse(&lines),
+			&lines,
+			&SvgStyle::default(),
+			&mut out,
+		)
+		.unwrap();
+		let out = String::from_utf8(out).unwrap();
+		assert!(
+			out.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="130" height="80">"#)
+		);
+		assert!(out.contains(r#"<rect x="15" y="30" width="20" height="40"/>"#));
+		assert!(out.contains(r#"<rect x="105" y="30" width="20" height="40"/>"#));
+		assert!(out
+			.contains(r#"<polyline points="35,50 45,50 95,50 105,50" marker-end="url(#head)"/>"#));
+		let texts: Vec<_> = out.lines().filter(|l| l.starts_with("<text")).collect();
+		assert_eq!(3, texts.len());
+		assert!(texts[0].starts_with(r#"<text x="60" y="30" textLength="20""#));
+		assert!(texts[0].ends_with(">go</text>"));
+	}
+
+	#[test]
+	fn test_html() {
+		let s = r#"
+ ,-.  go  ,-.
+ |a|----->|b|
+ '-'      '-'
+"#;
+		let lines = Lines(s.lines().map(|l| l.chars().collect()).collect());
+		let mut out = vec![];
+		html(&Diagram::parse(&lines), &lines, &mut out).unwrap();
+		let out = String::from_utf8(out).unwrap();
+		let pre: Vec<_> = out.lines().skip_while(|l| !l.starts_with("<pre")).collect();
+		assert_eq!(
+			vec![
+				r#"<pre class="graphedit">"#,
+				r#" <span class="box" data-box="0">,-.</span>  <span class="text">go</span>  <span class="box" data-box="1">,-.</span>"#,
+				r#" <span class="box" data-box="0">|</span><span class="text" data-box="0">a</span><span class="box" data-box="0">|</span><span class="edge" data-edge="0">-----&gt;</span><span class="box" data-box="1">|</span><span class="text" data-box="1">b</span><span class="box" data-box="1">|</span>"#,
+				r#" <span class="box" data-box="0">'-'</span>      <span class="box" data-box="1">'-'</span>"#,
+				"</pre>",
+			],
+			pre[..5]
+		);
+	}
+
+	#[test]
+	fn test_json() {
+		let s = r#"
+ ,-.  go  ,-.
+ |a|----->|b|
+ '-'      '-'  note "x"  more
+"#;
+		let lines = Lines(s.lines().map(|l| l.chars().collect()).collect());
+		let mut out = vec![];
+		json(&Diagram::parse(&lines), &lines, &mut out).unwrap();
+		let out = String::from_utf8(out).unwrap();
+		let out: Vec<_> = out.lines().collect();
+		assert_eq!(r#"  "version": 1,"#, out[1]);
+		assert_eq!(
+			r#"    {"id": 0, "tl": {"row": 1, "col": 1}, "br": {"row": 3, "col": 3}, "parent": null, "label": "a", "lines": [{"at": {"row": 2, "col": 2}, "text": "a"}]},"#,
+			out[3]
+		);
+		assert!(out[7].starts_with(
+			r#"    {"id": 0, "from": 0, "to": 1, "points": [{"row": 2, "col": 4}, {"row": 2, "col": 5}, "#
+		));
+		assert!(out[7].ends_with(
+			r#""head": {"glyph": ">", "dir": "right"}, "tail": null, "label": {"text": "go", "confidence": 1}}"#
+		));
+		assert_eq!(
+			r#"  "text": [{"at": {"row": 1, "col": 6}, "text": "go"}, {"at": {"row": 3, "col": 15}, "text": "note \"x\""}, {"at": {"row": 3, "col": 25}, "text": "more"}]"#,
+			out[9]
+		);
+	}
+}
@@ -0,0 +1,142 @@
+use crate::parse::Lines;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+
+// where a diagram came from, and enough about how it was written to put it
+// back the same way
+pub struct File {
+	pub path: Option<PathBuf>,
+	crlf: bool,
+	final_newline: bool,
+	// none of the original lines had trailing whitespace, so don't add any
+	trim: bool,
+}
+
+impl File {
+	// one that hasn't been written yet, so it gets plain unix line endings
+	pub fn new(path: Option<PathBuf>) -> File {
+		File {
+			path,
+			crlf: false,
+			final_newline: true,
+			trim: true,
+		}
+	}
+
+	pub fn open(path: PathBuf) -> io::Result<(File, Lines)> {
+		match std::fs::File::open(&path) {
+			Ok(f) => File::read(f, Some(path)),
+			// a new file, like any other editor would
+			Err(e) if e.kind() == io::ErrorKind::NotFound => {
+				Ok((File::new(Some(path)), Lines(vec![])))
+			}
+			Err(e) => Err(e),
+		}
+	}
+
+	pub fn read(mut r: impl Read, path: Option<PathBuf>) -> io::Result<(File, Lines)> {
+		let mut buf = String::new();
+		r.read_to_string(&mut buf)?;
+		let final_newline = buf.is_empty() || buf.ends_with('\n');
+		let crlf = buf.contains("\r\n");
+		let mut raw: Vec<&str> = buf.split('\n').collect();
+		if buf.is_empty() || buf.ends_with('\n') {
+			raw.pop();
+		}
+		let raw: Vec<&str> = raw
+			.into_iter()
+			.map(|l| l.strip_suffix('\r').unwrap_or(l))
+			.collect();
+		let trim = !raw.iter().any(|l| l.ends_with(char::is_whitespace));
+		let lines = Lines(raw.into_iter().map(|l| l.chars().collect()).collect());
+		Ok((
+			File {
+				path,
+				crlf,
+				final_newline,
+				trim,
+			},
+			lines,
+		))
+	}
+
+	pub fn write(&self, lines: &Lines, mut w: impl Write) -> io::Result<()> {
+		let eol = if self.crlf { "\r\n" } else { "\n" };
+		for (i, l) in lines.0.iter().enumerate() {
+			let l: String = l.iter().collect();
+			let l = if self.trim { l.trim_end() } else { &l };
+			w.write_all(l.as_bytes())?;
+			if i + 1 < lines.0.len() || self.final_newline {
+				w.write_all(eol.as_bytes())?;
+			}
+		}
+		w.flush()
+	}
+
+	pub fn save(&self, lines: &Lines) -> io::Result<()> {
+		let path = self
+			.path
+			.as_ref()
+			.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no file name"))?;
+		self.write(lines, io::BufWriter::new(std::fs::File::create(path)?))
+	}
+
+	// only takes the new name once something has been written under it
+	pub fn save_as(&mut self, lines: &Lines, path: PathBuf) -> io::Result<()> {
+		self.write(lines, io::BufWriter::new(std::fs::File::create(&path)?))?;
+		self.path = Some(path);
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn roundtrip(s: &str) -> String {
+		let (f, lines) = File::read(s.as_bytes(), None).unwrap();
+		let mut out = vec![];
+		f.write(&lines, &mut out).unwrap();
+		String::from_utf8(out).unwrap()
+	}
+
+	#[test]
+	fn test_roundtrip() {
+		for s in [
+			"",
+			",--.\n'--'\n",
+			",--.\r\n'--'\r\n",
+			",--.\n'--'",
+			",--.  \n'--'\n",
+		] {
+			assert_eq!(s, roundtrip(s));
+		}
+	}
+
+	#[test]
+	fn test_trim() {
+		let (f, mut lines) = File::read(",--.\n'--'\n".as_bytes(), None).unwrap();
+		lines.set((1, 6).into(), ' ');
+		let mut out = vec![];
+		f.write(&lines, &mut out).unwrap();
+		assert_eq!(",--.\n'--'\n", String::from_utf8(out).unwrap());
+	}
+
+	#[test]
+	fn test_new() {
+		let lines = Lines(vec![",--.  ".chars().collect(), "'--'".chars().collect()]);
+		let mut out = vec![];
+		File::new(None).write(&lines, &mut out).unwrap();
+		assert_eq!(",--.\n'--'\n", String::from_utf8(out).unwrap());
+	}
+
+	#[test]
+	fn test_save_as_fails() {
+		let (mut f, lines) = File::read(",--.\n'--'\n".as_bytes(), Some("a.txt".into())).unwrap();
+		let bad = std::env::temp_dir()
+			.join("graphedit-no-such-dir")
+			.join("b.txt");
+		assert!(f.save_as(&lines, bad).is_err());
+		assert_eq!(Some("a.txt".into()), f.path);
+	}
+}
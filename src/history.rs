@@ -0,0 +1,87 @@
+// undo/redo stacks of whole editor states, diagrams are small enough that
+// keeping copies is simpler than keeping diffs
+//
+// every state gets a generation number, so going back to the one that was
+// saved can be told apart from an edit that happens to land at the same depth
+pub struct History<T> {
+	undo: Vec<(T, usize)>,
+	redo: Vec<(T, usize)>,
+	gen: usize,
+	next: usize,
+}
+
+impl<T> History<T> {
+	pub fn new() -> History<T> {
+		History {
+			undo: vec![],
+			redo: vec![],
+			gen: 0,
+			next: 1,
+		}
+	}
+
+	// which state the editor is in now
+	pub fn generation(&self) -> usize {
+		self.gen
+	}
+
+	// remember the state from just before an edit
+	pub fn record(&mut self, before: T) {
+		self.undo.push((before, self.gen));
+		self.redo.clear();
+		self.gen = self.next;
+		self.next += 1;
+	}
+
+	pub fn undo(&mut self, current: T) -> Option<T> {
+		let (prev, gen) = self.undo.pop()?;
+		self.redo.push((current, self.gen));
+		self.gen = gen;
+		Some(prev)
+	}
+
+	pub fn redo(&mut self, current: T) -> Option<T> {
+		let (next, gen) = self.redo.pop()?;
+		self.undo.push((current, self.gen));
+		self.gen = gen;
+		Some(next)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_undo_redo() {
+		let mut h = History::new();
+		h.record(0);
+		h.record(1);
+		assert_eq!(Some(1), h.undo(2));
+		assert_eq!(Some(0), h.undo(1));
+		assert_eq!(None, h.undo(0));
+		assert_eq!(Some(1), h.redo(0));
+		assert_eq!(Some(2), h.redo(1));
+		assert_eq!(None, h.redo(2));
+
+		// a new edit forgets anything that was undone
+		assert_eq!(Some(1), h.undo(2));
+		h.record(1);
+		assert_eq!(None, h.redo(3));
+	}
+
+	#[test]
+	fn test_generation() {
+		let mut h = History::new();
+		h.record(0);
+		let saved = h.generation();
+		h.record(1);
+		assert_ne!(saved, h.generation());
+		h.undo(2);
+		assert_eq!(saved, h.generation());
+		// back at the same depth, but not the saved state
+		h.undo(1);
+		h.record(0);
+		assert_ne!(saved, h.generation());
+	}
+}
@@ -0,0 +1,918 @@
+use crate::parse::*;
+
+// just the topology of a graph read from somewhere else, nodes in the order
+// they were first mentioned
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Graph {
+	pub nodes: Vec<Node>,
+	pub edges: Vec<GEdge>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+	pub id: String,
+	pub label: String,
+}
+
+// from and to index nodes, head is an arrowhead at the to end and tail one
+// at the from end
+#[derive(Debug, Clone, PartialEq)]
+pub struct GEdge {
+	pub from: usize,
+	pub to: usize,
+	pub head: bool,
+	pub tail: bool,
+	pub label: Option<String>,
+}
+
+impl Graph {
+	// the index of node id, adding it if this is the first we've heard of it
+	fn node(&mut self, id: &str) -> usize {
+		match self.nodes.iter().position(|n| n.id == id) {
+			Some(i) => i,
+			None => {
+				self.nodes.push(Node {
+					id: id.to_string(),
+					label: id.to_string(),
+				});
+				self.nodes.len() - 1
+			}
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+	Id(String),
+	Sym(&'static str),
+}
+
+fn dot_tokens(src: &str) -> Result<Vec<Tok>, String> {
+	let mut ret = vec![];
+	let cs: Vec<char> = src.chars().collect();
+	let mut i = 0;
+	while i < cs.len() {
+		let c = cs[i];
+		let rest: String = cs[i..cs.len().min(i + 2)].iter().collect();
+		if c.is_whitespace() {
+			i += 1;
+		} else if rest == "//" || (c == '#' && (i == 0 || cs[i - 1] == '\n')) {
+			while i < cs.len() && cs[i] != '\n' {
+				i += 1;
+			}
+		} else if rest == "/*" {
+			i += 2;
+			while i < cs.len() && !(cs[i] == '*' && cs.get(i + 1) == Some(&'/')) {
+				i += 1;
+			}
+			i += 2;
+		} else if rest == "->" || rest == "--" {
+			ret.push(Tok::Sym(if rest == "->" { "->" } else { "--" }));
+			i += 2;
+		} else if let Some(s) = ["{", "}", "[", "]", ";", ",", "=", ":"]
+			.into_iter()
+			.find(|s| s.starts_with(c))
+		{
+			ret.push(Tok::Sym(s));
+			i += 1;
+		} else if c == '"' {
+			let mut s = String::new();
+			i += 1;
+			while i < cs.len() && cs[i] != '"' {
+				match (cs[i], cs.get(i + 1)) {
+					('\\', Some('n' | 'l' | 'r')) => s.push('\n'),
+					('\\', Some('\n')) => {}
+					('\\', Some(&e)) => s.push(e),
+					(c, _) => s.push(c),
+				}
+				i += if cs[i] == '\\' { 2 } else { 1 };
+			}
+			if i >= cs.len() {
+				return Err("unterminated string".to_string());
+			}
+			i += 1;
+			ret.push(Tok::Id(s));
+		} else if c == '<' {
+			// an html label, kept as it is
+			let (mut depth, start) = (0, i);
+			while i < cs.len() {
+				match cs[i] {
+					'<' => depth += 1,
+					'>' => depth -= 1,
+					_ => {}
+				}
+				i += 1;
+				if depth == 0 {
+					break;
+				}
+			}
+			if depth != 0 {
+				return Err("unterminated html label".to_string());
+			}
+			ret.push(Tok::Id(cs[start + 1..i - 1].iter().collect()));
+		} else if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' {
+			let start = i;
+			while i < cs.len() && (cs[i].is_alphanumeric() || cs[i] == '_' || cs[i] == '.') {
+				i += 1;
+			}
+			i = i.max(start + 1);
+			ret.push(Tok::Id(cs[start..i].iter().collect()));
+		} else {
+			return Err(format!("unexpected {:?}", c));
+		}
+	}
+	Ok(ret)
+}
+
+struct DotParser {
+	toks: Vec<Tok>,
+	at: usize,
+	directed: bool,
+	graph: Graph,
+	// what node [..] and edge [..] have said so far in this subgraph
+	node_attrs: Vec<(String, String)>,
+	edge_attrs: Vec<(String, String)>,
+	// every node named so far, for an edge to a subgraph to go to all of them
+	named: Vec<usize>,
+}
+
+impl DotParser {
+	fn peek(&self) -> Option<&Tok> {
+		self.toks.get(self.at)
+	}
+
+	fn sym(&mut self, s: &str) -> bool {
+		if matches!(self.peek(), Some(Tok::Sym(t)) if *t == s) {
+			self.at += 1;
+			true
+		} else {
+			false
+		}
+	}
+
+	fn expect(&mut self, s: &str) -> Result<(), String> {
+		if self.sym(s) {
+			Ok(())
+		} else {
+			Err(format!("expected {:?}, found {:?}", s, self.peek()))
+		}
+	}
+
+	fn id(&mut self) -> Option<String> {
+		match self.peek() {
+			Some(Tok::Id(s)) => {
+				let s = s.clone();
+				self.at += 1;
+				Some(s)
+			}
+			_ => None,
+		}
+	}
+
+	// [a=b, c=d; e=f][g=h]
+	fn attrs(&mut self) -> Result<Vec<(String, String)>, String> {
+		let mut ret = vec![];
+		while self.sym("[") {
+			while !self.sym("]") {
+				let k = self.id().ok_or("expected an attribute")?;
+				self.expect("=")?;
+				let v = self.id().ok_or("expected an attribute value")?;
+				ret.push((k, v));
+				let _ = self.sym(",") || self.sym(";");
+			}
+		}
+		Ok(ret)
+	}
+
+	// a node id, dropping any :port
+	fn node_id(&mut self) -> Result<String, String> {
+		let id = self
+			.id()
+			.ok_or_else(|| format!("expected a node, found {:?}", self.peek()))?;
+		while self.sym(":") {
+			self.id().ok_or("expected a port")?;
+		}
+		Ok(id)
+	}
+
+	// the index of node id, which picks up the node defaults if it's new
+	fn node(&mut self, id: &str) -> usize {
+		let new = self.graph.nodes.len();
+		let n = self.graph.node(id);
+		if n == new {
+			if let Some((_, l)) = self.node_attrs.iter().rev().find(|(k, _)| k == "label") {
+				self.graph.nodes[n].label = l.clone();
+			}
+		}
+		self.named.push(n);
+		n
+	}
+
+	// the statements up to the next }, with the defaults put back after
+	// them, returns the nodes they named
+	fn subgraph(&mut self) -> Result<Vec<usize>, String> {
+		let defaults = (self.node_attrs.clone(), self.edge_attrs.clone());
+		let start = self.named.len();
+		self.stmts()?;
+		(self.node_attrs, self.edge_attrs) = defaults;
+		let mut ret = self.named[start..].to_vec();
+		ret.sort();
+		ret.dedup();
+		Ok(ret)
+	}
+
+	// a node, or a subgraph standing for every node in it
+	fn endpoint(&mut self) -> Result<Vec<usize>, String> {
+		if self.peek() == Some(&Tok::Id("subgraph".to_string())) {
+			self.at += 1;
+			let _ = self.id();
+			self.expect("{")?;
+			return self.subgraph();
+		}
+		if self.sym("{") {
+			return self.subgraph();
+		}
+		let id = self.node_id()?;
+		Ok(vec![self.node(&id)])
+	}
+
+	fn stmts(&mut self) -> Result<(), String> {
+		while !self.sym("}") {
+			if self.peek().is_none() {
+				return Err("missing }".to_string());
+			}
+			self.stmt()?;
+			let _ = self.sym(";");
+		}
+		Ok(())
+	}
+
+	fn stmt(&mut self) -> Result<(), String> {
+		let next = self.toks.get(self.at + 1).cloned();
+		if let Some(Tok::Id(kw)) = self.peek().cloned() {
+			if next == Some(Tok::Sym("[")) && matches!(kw.as_str(), "graph" | "node" | "edge") {
+				self.at += 1;
+				let attrs = self.attrs()?;
+				match kw.as_str() {
+					"node" => self.node_attrs.extend(attrs),
+					"edge" => self.edge_attrs.extend(attrs),
+					_ => {}
+				}
+				return Ok(());
+			}
+			if next == Some(Tok::Sym("=")) {
+				self.at += 2;
+				self.id().ok_or("expected a value")?;
+				return Ok(());
+			}
+		}
+		// subgraphs only group nodes, which get drawn flat anyway
+		let mut chain = vec![self.endpoint()?];
+		while self.sym("->") || self.sym("--") {
+			chain.push(self.endpoint()?);
+		}
+		let attrs = self.attrs()?;
+		if chain.len() == 1 {
+			if let Some((_, l)) = attrs.iter().rev().find(|(k, _)| k == "label") {
+				for n in &chain[0] {
+					self.graph.nodes[*n].label = l.clone();
+				}
+			}
+			return Ok(());
+		}
+		let attrs = [self.edge_attrs.clone(), attrs].concat();
+		let attr = |k: &str| {
+			attrs
+				.iter()
+				.rev()
+				.find(|(a, _)| a == k)
+				.map(|(_, v)| v.clone())
+		};
+		let (head, tail) = match attr("dir").as_deref() {
+			Some("both") => (true, true),
+			Some("back") => (false, true),
+			Some("none") => (false, false),
+			Some(_) => (true, false),
+			None => (self.directed, false),
+		};
+		for w in chain.windows(2) {
+			for &from in &w[0] {
+				for &to in &w[1] {
+					self.graph.edges.push(GEdge {
+						from,
+						to,
+						head,
+						tail,
+						label: attr("label"),
+					});
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+// the parts of the dot language that say what's connected to what, anything
+// about how it looks is skipped
+pub fn dot(src: &str) -> Result<Graph, String> {
+	let mut p = DotParser {
+		toks: dot_tokens(src)?,
+		at: 0,
+		directed: false,
+		graph: Graph::default(),
+		node_attrs: vec![],
+		edge_attrs: vec![],
+		named: vec![],
+	};
+	let mut kw = p.id().ok_or("expected graph or digraph")?;
+	if kw == "strict" {
+		kw = p.id().ok_or("expected graph or digraph")?;
+	}
+	p.directed = match kw.as_str() {
+		"digraph" => true,
+		"graph" => false,
+		_ => return Err(format!("expected graph or digraph, found {:?}", kw)),
+	};
+	let _ = p.id();
+	p.expect("{")?;
+	p.stmts()?;
+	Ok(p.graph)
+}
+
+// a node, with its label if it has a shape like a[label] or b((label))
+fn mermaid_node(g: &mut Graph, s: &str) -> Result<(usize, usize), String> {
+	let id_len = s
+		.find(|c: char| !(c.is_alphanumeric() || c == '_'))
+		.unwrap_or(s.len());
+	if id_len == 0 {
+		return Err(format!("expected a node at {:?}", s));
+	}
+	let n = g.node(&s[..id_len]);
+	let rest = &s[id_len..];
+	let open = rest
+		.find(|c: char| !"[({>/\\".contains(c))
+		.unwrap_or(rest.len());
+	if open == 0 {
+		return Ok((n, id_len));
+	}
+	// the label ends at the bracket pairing with the innermost opening one,
+	// so brackets inside it (or quoted) stay part of the label
+	let (inner, shut) = match rest[..open].chars().rev().find(|c| !"/\\".contains(*c)) {
+		Some('(') => ('(', ')'),
+		Some('{') => ('{', '}'),
+		_ => ('[', ']'),
+	};
+	let mut depth = 0;
+	let mut quoted = false;
+	let close = rest[open..]
+		.char_indices()
+		.find(|&(_, c)| {
+			match c {
+				'"' => quoted = !quoted,
+				_ if quoted => {}
+				c if c == inner => depth += 1,
+				c if c == shut && depth == 0 => return true,
+				c if c == shut => depth -= 1,
+				_ => {}
+			}
+			false
+		})
+		.ok_or_else(|| format!("unclosed node shape at {:?}", s))?
+		.0 + open;
+	g.nodes[n].label = mermaid_text(rest[open..close].trim_end_matches(['/', '\\']));
+	let end = rest[close..]
+		.find(|c: char| !"])}".contains(c))
+		.unwrap_or(rest.len() - close)
+		+ close;
+	Ok((n, id_len + end))
+}
+
+// a label as written in a chart, maybe quoted, with <br> for line breaks and
+// entities like #quot; or #59; for characters that would end it early
+fn mermaid_text(s: &str) -> String {
+	let s = s.trim().trim_matches('"').replace("<br>", "\n");
+	let mut ret = String::new();
+	let mut rest = s.as_str();
+	while let Some(i) = rest.find('#') {
+		ret.push_str(&rest[..i]);
+		rest = &rest[i..];
+		let c = rest.find(';').and_then(|end| {
+			let c = match &rest[1..end] {
+				"quot" => Some('"'),
+				"amp" => Some('&'),
+				"lt" => Some('<'),
+				"gt" => Some('>'),
+				n => n.parse().ok().and_then(char::from_u32),
+			};
+			c.map(|c| (c, end + 1))
+		});
+		match c {
+			Some((c, n)) => {
+				ret.push(c);
+				rest = &rest[n..];
+			}
+			None => {
+				ret.push('#');
+				rest = &rest[1..];
+			}
+		}
+	}
+	ret.push_str(rest);
+	ret
+}
+
+// a link like -->, ---, <-->, -.->, ==>, -- text -->, -. text .-> or
+// == text ==>, and an optional |text| after it, as (head, tail, label, length)
+fn mermaid_link(s: &str) -> Option<(bool, bool, Option<String>, usize)> {
+	let line = |c: char| c == '-' || c == '.' || c == '=';
+	let tail = s.starts_with('<');
+	let body = s[tail as usize..]
+		.find(|c: char| !line(c))
+		.unwrap_or(s.len() - tail as usize);
+	if body < 2 {
+		return None;
+	}
+	let mut len = tail as usize + body;
+	let mut head = s[len..].starts_with('>');
+	len += head as usize;
+	let mut label = None;
+	if !head && body == 2 && s[len..].starts_with(' ') {
+		// the text runs up to the other half of the link, -. pairs with .->
+		let close = match &s[tail as usize..len] {
+			"-." => ".-",
+			open => open,
+		};
+		let rest = &s[len..];
+		let at = rest.find(close)?;
+		label = Some(mermaid_text(&rest[..at]));
+		let end = at + close.len();
+		let end = rest[end..]
+			.find(|c: char| !line(c))
+			.map_or(rest.len(), |n| n + end);
+		head = rest[end..].starts_with('>');
+		len += end + head as usize;
+	}
+	let rest = &s[len..];
+	let trimmed = rest.trim_start();
+	if let Some(t) = trimmed.strip_prefix('|') {
+		let close = t.find('|')?;
+		label = Some(mermaid_text(&t[..close]));
+		len += rest.len() - trimmed.len() + close + 2;
+	}
+	Some((head, tail, label, len))
+}
+
+// the statements in a chart, one per line or split at semicolons, except
+// ones in a quote, a node's brackets, an edge's |label| or ending an entity
+//
+//  A["x;y"] -->|#quot;z#quot;| B; B --> C
+//                                ^ the only split
+fn mermaid_statements(src: &str) -> Vec<&str> {
+	let mut ret = vec![];
+	for l in src.lines() {
+		let (mut depth, mut quoted, mut piped) = (0, false, false);
+		let mut start = 0;
+		for (i, c) in l.char_indices() {
+			let entity = l[start..i]
+				.rsplit_once('#')
+				.is_some_and(|(_, e)| !e.is_empty() && e.chars().all(char::is_alphanumeric));
+			match c {
+				'"' => quoted = !quoted,
+				_ if quoted => {}
+				'[' | '(' | '{' => depth += 1,
+				// a > opens a shape too, but it can't be told from an arrow
+				']' | ')' | '}' => depth = (depth - 1).max(0),
+				'|' if depth == 0 => piped = !piped,
+				';' if depth == 0 && !piped && !entity => {
+					ret.push(&l[start..i]);
+					start = i + 1;
+				}
+				_ => {}
+			}
+		}
+		ret.push(&l[start..]);
+	}
+	ret
+}
+
+// a mermaid flowchart, subgraphs are flattened and styling is skipped
+pub fn mermaid(src: &str) -> Result<Graph, String> {
+	let mut g = Graph::default();
+	let mut lines = mermaid_statements(src)
+		.into_iter()
+		.map(str::trim)
+		.filter(|l| !l.is_empty() && !l.starts_with("%%"));
+	match lines.next() {
+		Some(l) if l.starts_with("flowchart") || l.starts_with("graph") => {}
+		l => return Err(format!("expected flowchart, found {:?}", l)),
+	}
+	for l in lines {
+		let kw = l.split_whitespace().next().unwrap_or("");
+		let skip = [
+			"subgraph",
+			"end",
+			"direction",
+			"classDef",
+			"class",
+			"style",
+			"linkStyle",
+			"click",
+		];
+		if skip.contains(&kw) {
+			continue;
+		}
+		let (mut from, n) = mermaid_node(&mut g, l)?;
+		let mut rest = l[n..].trim_start();
+		while !rest.is_empty() {
+			let (head, tail, label, n) =
+				mermaid_link(rest).ok_or_else(|| format!("expected a link at {:?}", rest))?;
+			rest = rest[n..].trim_start();
+			let (to, n) = mermaid_node(&mut g, rest)?;
+			rest = rest[n..].trim_start();
+			g.edges.push(GEdge {
+				from,
+				to,
+				head,
+				tail,
+				label,
+			});
+			from = to;
+		}
+	}
+	Ok(g)
+}
+
+// lay a graph out as a row of boxes with every edge in its own lane
+// underneath, leaving each box by its own column of the bottom border
+//
+//  ,---.  ,-------.  ,---.
+//  | a |  |   b   |  | c |
+//  '---'  '-------'  '---'
+//    | |    ^   ^      ^
+//    | '----'   '------'
+//    |           go    |
+//    '-----------------'
+//
+// a labelled edge keeps clear the columns after its left hand end, so no other
+// edge's line runs past where its label goes
+pub fn draw(g: &Graph) -> Lines {
+	let label_len = |e: &GEdge| e.label.as_ref().map(|l| l.chars().count());
+	// every end of every edge needs a column of its own, and a label room for
+	// half its length more
+	let mut ends = vec![0; g.nodes.len()];
+	for e in &g.edges {
+		ends[e.from] += 1;
+		ends[e.to] += 1;
+		if let Some(n) = label_len(e) {
+			ends[e.from.min(e.to)] += n.div_ceil(2);
+		}
+	}
+	let label_lines = |n: &Node| -> Vec<String> { n.label.lines().map(String::from).collect() };
+	let mut left = vec![];
+	let mut col = 0;
+	for (i, n) in g.nodes.iter().enumerate() {
+		let text = label_lines(n)
+			.iter()
+			.map(|l| l.chars().count())
+			.max()
+			.unwrap_or(0);
+		let inner = (text + 2).max(2 * ends[i] + 1);
+		left.push((col, inner));
+		col += inner + 4;
+	}
+	let height = |n: &Node| label_lines(n).len().max(1) + 2;
+	let bottom = g.nodes.iter().map(height).max().unwrap_or(0);
+
+	let mut lines = Lines(vec![]);
+	for (i, n) in g.nodes.iter().enumerate() {
+		let (l, inner) = left[i];
+		let h = height(n);
+		let r = l + inner + 1;
+		for c in l..=r {
+			lines.set(Point::from((0, c)), '-');
+			lines.set(Point::from((h - 1, c)), '-');
+		}
+		for row in 0..h {
+			lines.set(Point::from((row, l)), '|');
+			lines.set(Point::from((row, r)), '|');
+		}
+		lines.set(Point::from((0, l)), ',');
+		lines.set(Point::from((0, r)), '.');
+		lines.set(Point::from((h - 1, l)), '\'');
+		lines.set(Point::from((h - 1, r)), '\'');
+		for (row, text) in label_lines(n).iter().enumerate() {
+			for (j, c) in text.chars().enumerate() {
+				lines.set(Point::from((row + 1, l + 2 + j)), c);
+			}
+		}
+	}
+
+	// lanes in the order the edges came, a blank row between lanes and room
+	// for a label under any that have one
+	let mut used = vec![0; g.nodes.len()];
+	// the next free column along n's bottom, keeping skip more spare after it
+	let mut port = |n: usize, skip: usize| {
+		used[n] += 1;
+		let col = left[n].0 + 2 * used[n] - 1;
+		used[n] += skip;
+		col
+	};
+	let mut lane = bottom + 1;
+	let mut verticals = vec![];
+	let mut lanes = vec![];
+	for e in &g.edges {
+		let skip = label_len(e).map_or(0, |n| n.div_ceil(2));
+		let a = port(e.from, if e.from <= e.to { skip } else { 0 });
+		let b = port(e.to, if e.to < e.from { skip } else { 0 });
+		let (lo, hi) = (a.min(b), a.max(b));
+		for c in lo..=hi {
+			lines.set(Point::from((lane, c)), '-');
+		}
+		lines.set(Point::from((lane, lo)), '\'');
+		lines.set(Point::from((lane, hi)), '\'');
+		verticals.push((a, height(&g.nodes[e.from]), lane, e.tail));
+		verticals.push((b, height(&g.nodes[e.to]), lane, e.head));
+		lanes.push((lo, hi, lane, e.label.clone()));
+		lane += if e.label.is_some() { 3 } else { 2 };
+	}
+	for (col, top, lane, arrow) in verticals {
+		for row in top..lane {
+			lines.set(Point::from((row, col)), '|');
+		}
+		if arrow {
+			lines.set(Point::from((top, col)), '^');
+		}
+	}
+	// a label goes under its lane, somewhere no other edge's line goes past
+	for (lo, hi, lane, label) in lanes {
+		let label = match label {
+			Some(l) => l.replace('\n', " "),
+			None => continue,
+		};
+		let n = label.chars().count();
+		let below = lines.0.get(lane + 1).cloned().unwrap_or_default();
+		let free = |c: usize| below.get(c).is_none_or(|c| *c == ' ');
+		let at = (lo + 1..hi.max(lo + 2)).find(|c| (c - 1..c + n + 1).all(free));
+		if let Some(at) = at {
+			for (j, c) in label.chars().enumerate() {
+				lines.set(Point::from((lane + 1, at + j)), c);
+			}
+		}
+	}
+	lines
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::diagram::Diagram;
+
+	// what the drawing says is connected, by label, to compare with the graph
+	// it was drawn from
+	fn topology(g: &Graph) -> Vec<(String, String, bool, bool, Option<String>)> {
+		let mut ret: Vec<_> = g
+			.edges
+			.iter()
+			.map(|e| {
+				(
+					g.nodes[e.from].label.clone(),
+					g.nodes[e.to].label.clone(),
+					e.head,
+					e.tail,
+					e.label.clone(),
+				)
+			})
+			.collect();
+		ret.sort();
+		ret
+	}
+
+	fn round_trip(g: &Graph) {
+		let dia = Diagram::parse(&draw(g));
+		let mut labels: Vec<_> = dia.labels.iter().map(|l| l.text()).collect();
+		let mut want: Vec<_> = g.nodes.iter().map(|n| n.label.clone()).collect();
+		labels.sort();
+		want.sort();
+		assert_eq!(want, labels);
+		let mut edges: Vec<_> = dia
+			.edges
+			.iter()
+			.map(|e| {
+				(
+					dia.labels[e.from.unwrap()].text(),
+					dia.labels[e.to.unwrap()].text(),
+					e.head.is_some(),
+					e.tail.is_some(),
+					e.label.as_ref().map(|l| l.text.text()),
+				)
+			})
+			.collect();
+		edges.sort();
+		// an undirected or two way edge can come back either way round
+		let flip = |es: Vec<(String, String, bool, bool, Option<String>)>| {
+			let mut es: Vec<_> = es
+				.into_iter()
+				.map(|(a, b, h, t, l)| match (h, t) {
+					(false, false) | (true, true) if b < a => (b, a, h, t, l),
+					(false, true) => (b, a, true, false, l),
+					_ => (a, b, h, t, l),
+				})
+				.collect();
+			es.sort();
+			es
+		};
+		assert_eq!(flip(topology(g)), flip(edges));
+	}
+
+	#[test]
+	fn test_dot() {
+		let g = dot(r#"
+digraph G {
+	// styling is ignored
+	rankdir=LR;
+	node [shape=box];
+	a [label="start"];
+	b [label="two\nlines"];
+	a -> b [label="go"];
+	b -> c -> a;
+	c -> d [dir=both];
+	d -> a [dir=back];
+	subgraph cluster_x { e; }
+}
+"#)
+		.unwrap();
+		assert_eq!(
+			vec!["start", "two\nlines", "c", "d", "e"],
+			g.nodes.iter().map(|n| n.label.as_str()).collect::<Vec<_>>()
+		);
+		assert_eq!(
+			GEdge {
+				from: 0,
+				to: 1,
+				head: true,
+				tail: false,
+				label: Some("go".to_string()),
+			},
+			g.edges[0]
+		);
+		assert_eq!(5, g.edges.len());
+		round_trip(&g);
+
+		let g = dot("graph { a -- b; b -- c }").unwrap();
+		assert!(g.edges.iter().all(|e| !e.head && !e.tail));
+		round_trip(&g);
+	}
+
+	#[test]
+	fn test_dot_defaults() {
+		let g = dot(r#"
+digraph {
+	edge [dir=none];
+	a -> {b c};
+	subgraph {
+		node [label="inner"];
+		edge [label="in", dir=forward];
+		{d e} -> f;
+	}
+	g -> a;
+}
+"#)
+		.unwrap();
+		assert_eq!(
+			vec!["a", "b", "c", "inner", "inner", "inner", "g"],
+			g.nodes.iter().map(|n| n.label.as_str()).collect::<Vec<_>>()
+		);
+		assert_eq!(
+			vec![
+				(0, 1, false, None),
+				(0, 2, false, None),
+				(3, 5, true, Some("in")),
+				(4, 5, true, Some("in")),
+				(6, 0, false, None),
+			],
+			g.edges
+				.iter()
+				.map(|e| (e.from, e.to, e.head, e.label.as_deref()))
+				.collect::<Vec<_>>()
+		);
+	}
+
+	#[test]
+	fn test_mermaid() {
+		let g = mermaid(
+			r#"flowchart LR
+	%% a comment
+	A[Start here] -->|yes| B(Next)
+	B --- C((circle)) <--> A
+	subgraph s [x]
+	C -- via --> D{"q?"}
+	end
+	D -.-> A; D ==> B
+"#,
+		)
+		.unwrap();
+		assert_eq!(
+			vec!["Start here", "Next", "circle", "q?"],
+			g.nodes.iter().map(|n| n.label.as_str()).collect::<Vec<_>>()
+		);
+		assert_eq!(
+			vec![
+				(0, 1, true, false, Some("yes")),
+				(1, 2, false, false, None),
+				(2, 0, true, true, None),
+				(2, 3, true, false, Some("via")),
+				(3, 0, true, false, None),
+				(3, 1, true, false, None),
+			],
+			g.edges
+				.iter()
+				.map(|e| (e.from, e.to, e.head, e.tail, e.label.as_deref()))
+				.collect::<Vec<_>>()
+		);
+		round_trip(&g);
+	}
+
+	#[test]
+	fn test_mermaid_brackets() {
+		let g = mermaid(
+			r#"graph TD
+	A[x (y)] --> B((a (b) c))
+	B --> C{"}"} --> D[/p [q]/] --> E>r]
+"#,
+		)
+		.unwrap();
+		assert_eq!(
+			vec!["x (y)", "a (b) c", "}", "p [q]", "r"],
+			g.nodes.iter().map(|n| n.label.as_str()).collect::<Vec<_>>()
+		);
+		assert_eq!(4, g.edges.len());
+		assert!(mermaid("graph TD\n\tA[x [y] --> B").is_err());
+	}
+
+	#[test]
+	fn test_mermaid_semicolons() {
+		let g = mermaid(
+			r#"graph LR; A["x; y"] -->|#quot;a;b#quot;| B(p;q); B -. maybe .-> C
+	C -- #quot;so#quot; --> A; C -.- D
+"#,
+		)
+		.unwrap();
+		assert_eq!(
+			vec!["x; y", "p;q", "C", "D"],
+			g.nodes.iter().map(|n| n.label.as_str()).collect::<Vec<_>>()
+		);
+		assert_eq!(
+			vec![
+				(0, 1, true, Some("\"a;b\"")),
+				(1, 2, true, Some("maybe")),
+				(2, 0, true, Some("\"so\"")),
+				(2, 3, false, None),
+			],
+			g.edges
+				.iter()
+				.map(|e| (e.from, e.to, e.head, e.label.as_deref()))
+				.collect::<Vec<_>>()
+		);
+	}
+
+	#[test]
+	fn test_glyph_labels_round_trip() {
+		// labels made of (or with) line glyphs, under a horizontal lane
+		for l in ["-", "v", "a|b"] {
+			let g = dot(&format!(r#"digraph {{ a -> b [label="{}"]; }}"#, l)).unwrap();
+			round_trip(&g);
+		}
+	}
+
+	#[test]
+	fn test_dot_errors() {
+		assert_eq!(
+			Err("unterminated html label".to_string()),
+			dot("graph {a <")
+		);
+		assert_eq!(
+			Err("unterminated html label".to_string()),
+			dot("graph {a [label=<<b>x</b>]}")
+		);
+	}
+
+	#[test]
+	fn test_labels_round_trip() {
+		// labels on loops, on edges both ways and on edges side by side all
+		// need somewhere only their own edge goes past
+		let g = dot(r#"
+digraph {
+	a -> a [label="again"];
+	a -> b [label="one"];
+	a -> b [label="two"];
+	b -> a [label="back"];
+	b -> b;
+	c -> b [label="long label here"];
+	c -> c [label="x"];
+}
+"#)
+		.unwrap();
+		assert_eq!(7, g.edges.len());
+		round_trip(&g);
+	}
+}
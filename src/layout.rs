@@ -0,0 +1,736 @@
+use crate::diagram::Diagram;
+use crate::import::{GEdge, Graph, Node};
+use crate::parse::*;
+use std::collections::HashMap;
+
+// something that takes up a column or more of a layer, either a node's box
+// or a dummy standing in for an edge passing through on its way further down
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Item {
+	Node(usize),
+	Dummy,
+}
+
+// one hop of an edge between neighbouring layers, or a self loop hanging
+// below its box
+#[derive(Debug, Clone, Copy)]
+struct Seg {
+	up: usize,
+	down: usize,
+	edge: usize,
+	looped: bool,
+}
+
+// a drawing's boxes and the edges between them, nesting and dangling
+// edges don't survive
+impl From<&Diagram> for Graph {
+	fn from(dia: &Diagram) -> Graph {
+		Graph {
+			nodes: (0..dia.boxes.len())
+				.map(|i| Node {
+					id: dia.name(i),
+					label: dia.labels[i].text(),
+				})
+				.collect(),
+			edges: dia
+				.edges
+				.iter()
+				.filter_map(|e| {
+					Some(GEdge {
+						from: e.from?,
+						to: e.to?,
+						head: e.head.is_some(),
+						tail: e.tail.is_some(),
+						label: e.label.as_ref().map(|l| l.text.text()),
+					})
+				})
+				.collect(),
+		}
+	}
+}
+
+// what a Graph made from dia would leave out of it, if anything: edges
+// missing an end, boxes inside other boxes and text that isn't a label
+pub fn left_out(dia: &Diagram, lines: &Lines) -> Option<&'static str> {
+	if dia.edges.iter().any(|e| e.from.is_none() || e.to.is_none()) {
+		return Some("dangling edges");
+	}
+	if dia.parents.iter().any(|p| p.is_some()) {
+		return Some("boxes inside boxes");
+	}
+	let labelled = |p: Point| {
+		dia.edges.iter().filter_map(|e| e.label.as_ref()).any(|l| {
+			l.text.0.iter().any(|(at, s)| {
+				at.row == p.row && p.col >= at.col && p.col < at.col + s.chars().count()
+			})
+		})
+	};
+	let free = (0..lines.0.len())
+		.flat_map(|row| (0..lines.0[row].len()).map(move |col| Point { row, col }))
+		.filter(|p| lines.at(*p).is_some_and(|c| c != ' '))
+		.any(|p| {
+			!dia.boxes.iter().any(|b| b.contains(p))
+				&& !dia.edges.iter().any(|e| path_contains(&e.path, p))
+				&& !labelled(p)
+		});
+	free.then_some("free text")
+}
+
+// lay a graph out in layers down the page, sugiyama style:
+//
+//  1. turn edges around until there are no cycles, so everything flows down
+//  2. put each node a layer below the lowest node pointing at it
+//  3. give edges that skip layers a dummy in each layer they pass through
+//  4. reorder each layer by where its neighbours are, keeping whichever
+//     order crosses least
+//  5. place boxes under the things they're connected to
+//
+//        ,---.
+//        | a |
+//        '---'
+//         | |
+//    ,----' '---.
+//    v          v
+//  ,---.      ,---.
+//  | b |      | c |
+//  '---'      '---'
+//
+// ports (where an edge meets a box) are four columns apart, and alternate
+// layers are offset by two, so lines between two layers never run into each
+// other or cross right next to a corner
+//
+// a label needs a spot only its own edge goes past, and when a tight
+// layout hasn't got one everything is spread out a bit more and tried again
+pub fn layout(g: &Graph) -> Result<Lines, String> {
+	(0..8)
+		.find_map(|gap| spaced(g, gap))
+		.ok_or_else(|| "no room for every edge label".to_string())
+}
+
+// a layout with gap extra steps of four columns between ports and between
+// boxes, and gap extra rows under each label, or None if a label didn't fit
+fn spaced(g: &Graph, gap: usize) -> Option<Lines> {
+	let n = g.nodes.len();
+	let loops: Vec<usize> = (0..g.edges.len())
+		.filter(|e| g.edges[*e].from == g.edges[*e].to)
+		.collect();
+	// (upper, lower) for everything else, pointing the way the arrow does
+	let mut flow: Vec<(usize, usize, usize)> = g
+		.edges
+		.iter()
+		.enumerate()
+		.filter(|(_, e)| e.from != e.to)
+		.map(|(i, e)| match (e.head, e.tail) {
+			(false, true) => (e.to, e.from, i),
+			_ => (e.from, e.to, i),
+		})
+		.collect();
+
+	// 1. anything pointing back at a node we're still exploring closes a cycle
+	let mut state = vec![0u8; n];
+	for root in 0..n {
+		if state[root] != 0 {
+			continue;
+		}
+		let mut stack = vec![(root, 0)];
+		state[root] = 1;
+		while let Some((v, next)) = stack.pop() {
+			match flow.iter().enumerate().skip(next).find(|(_, f)| f.0 == v) {
+				Some((fi, &(_, w, _))) => {
+					stack.push((v, fi + 1));
+					match state[w] {
+						0 => {
+							state[w] = 1;
+							stack.push((w, 0));
+						}
+						1 => flow[fi] = (w, v, flow[fi].2),
+						_ => {}
+					}
+				}
+				None => state[v] = 2,
+			}
+		}
+	}
+
+	// 2. longest path from the top
+	let mut rank = vec![0; n];
+	let mut indeg = vec![0; n];
+	for f in &flow {
+		indeg[f.1] += 1;
+	}
+	let mut ready: Vec<usize> = (0..n).filter(|v| indeg[*v] == 0).collect();
+	while let Some(v) = ready.pop() {
+		for f in flow.iter().filter(|f| f.0 == v) {
+			rank[f.1] = rank[f.1].max(rank[v] + 1);
+			indeg[f.1] -= 1;
+			if indeg[f.1] == 0 {
+				ready.push(f.1);
+			}
+		}
+	}
+
+	// 3. dummies, item i < n is node i
+	let mut items: Vec<Item> = (0..n).map(Item::Node).collect();
+	let mut layer_of = rank.clone();
+	let mut segs = vec![];
+	for &(u, v, edge) in &flow {
+		let mut up = u;
+		for l in rank[u] + 1..rank[v] {
+			items.push(Item::Dummy);
+			layer_of.push(l);
+			let down = items.len() - 1;
+			segs.push(Seg {
+				up,
+				down,
+				edge,
+				looped: false,
+			});
+			up = down;
+		}
+		segs.push(Seg {
+			up,
+			down: v,
+			edge,
+			looped: false,
+		});
+	}
+	for &edge in &loops {
+		let v = g.edges[edge].from;
+		segs.push(Seg {
+			up: v,
+			down: v,
+			edge,
+			looped: true,
+		});
+	}
+	let depth = layer_of.iter().max().map_or(0, |l| l + 1);
+	let mut layers = vec![vec![]; depth];
+	for (i, l) in layer_of.iter().enumerate() {
+		layers[*l].push(i);
+	}
+
+	// 4. barycentre sweeps down then up
+	let mut pos = vec![0.0; items.len()];
+	let place = |layers: &[Vec<usize>], pos: &mut [f32]| {
+		for layer in layers {
+			for (p, i) in layer.iter().enumerate() {
+				pos[*i] = p as f32;
+			}
+		}
+	};
+	place(&layers, &mut pos);
+	let crossings = |pos: &[f32]| {
+		let hops: Vec<&Seg> = segs.iter().filter(|s| !s.looped).collect();
+		let mut ret = 0;
+		for (i, a) in hops.iter().enumerate() {
+			for b in &hops[i + 1..] {
+				if layer_of[a.up] == layer_of[b.up]
+					&& (pos[a.up] - pos[b.up]) * (pos[a.down] - pos[b.down]) < 0.0
+				{
+					ret += 1;
+				}
+			}
+		}
+		ret
+	};
+	let mut best = (crossings(&pos), layers.clone());
+	for sweep in 0..8 {
+		let down = sweep % 2 == 0;
+		let order: Vec<usize> = if down {
+			(1..depth).collect()
+		} else {
+			(0..depth.saturating_sub(1)).rev().collect()
+		};
+		for l in order {
+			let bary = |i: usize| {
+				let ns: Vec<f32> = segs
+					.iter()
+					.filter(|s| !s.looped)
+					.filter_map(|s| match down {
+						true if s.down == i => Some(pos[s.up]),
+						false if s.up == i => Some(pos[s.down]),
+						_ => None,
+					})
+					.collect();
+				if ns.is_empty() {
+					pos[i]
+				} else {
+					ns.iter().sum::<f32>() / ns.len() as f32
+				}
+			};
+			let mut keyed: Vec<(f32, usize)> = layers[l].iter().map(|i| (bary(*i), *i)).collect();
+			keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+			layers[l] = keyed.into_iter().map(|(_, i)| i).collect();
+			place(&layers, &mut pos);
+		}
+		let c = crossings(&pos);
+		if c < best.0 {
+			best = (c, layers.clone());
+		}
+	}
+	let layers = best.1;
+	place(&layers, &mut pos);
+
+	// 5. sizes, then left to right, each item as near as it can get to the
+	// middle of whatever it hangs off
+	let label_lines = |i: usize| -> Vec<String> {
+		match items[i] {
+			Item::Node(v) => g.nodes[v].label.lines().map(String::from).collect(),
+			Item::Dummy => vec![],
+		}
+	};
+	let label_width = |i: usize| {
+		label_lines(i)
+			.iter()
+			.map(|l| l.chars().count())
+			.max()
+			.unwrap_or(0)
+	};
+	let ups = |i: usize| segs.iter().filter(|s| s.down == i && !s.looped).count();
+	let downs = |i: usize| {
+		segs.iter()
+			.map(|s| {
+				if s.up != i {
+					0
+				} else if s.looped {
+					2
+				} else {
+					1
+				}
+			})
+			.sum::<usize>()
+	};
+	let phase = |i: usize| 2 * (layer_of[i] % 2);
+	// the right hand column of item i if its left is at l, and its ports
+	let span = |i: usize, l: usize| -> (usize, Vec<usize>) {
+		if items[i] == Item::Dummy {
+			let x = (l..).find(|x| x % 4 == phase(i)).unwrap();
+			return (x, vec![x]);
+		}
+		let first = (l + 2..).find(|x| x % 4 == phase(i)).unwrap();
+		let ports: Vec<usize> = (0..ups(i).max(downs(i)))
+			.map(|p| first + 4 * (gap + 1) * p)
+			.collect();
+		let r = (l + label_width(i) + 3).max(ports.last().map_or(0, |p| p + 2));
+		(r, ports)
+	};
+	let mut left = vec![0; items.len()];
+	let mut right = vec![0; items.len()];
+	let mut columns: Vec<Vec<usize>> = vec![vec![]; items.len()];
+	for layer in &layers {
+		let mut next = 0;
+		for &i in layer {
+			let above: Vec<usize> = segs
+				.iter()
+				.filter(|s| s.down == i && !s.looped)
+				.map(|s| (left[s.up] + right[s.up]) / 2)
+				.collect();
+			let want = match above.len() {
+				0 => next,
+				k => (above.iter().sum::<usize>() / k).saturating_sub((span(i, 0).0) / 2),
+			};
+			let l = want.max(next);
+			let (r, ports) = span(i, l);
+			let l = if items[i] == Item::Dummy { r } else { l };
+			left[i] = l;
+			right[i] = r;
+			columns[i] = ports;
+			next = r + 3 + 4 * gap;
+		}
+	}
+
+	// which port each segment uses, in the order of what's on the other end
+	// so they don't cross on the way out of the box
+	let mut bottom = vec![0; segs.len()];
+	let mut bottom2 = vec![0; segs.len()];
+	let mut top = vec![0; segs.len()];
+	for i in 0..items.len() {
+		let mut out: Vec<usize> = (0..segs.len()).filter(|s| segs[*s].up == i).collect();
+		out.sort_by(|a, b| {
+			let key = |s: usize| {
+				if segs[s].looped {
+					f32::MAX
+				} else {
+					pos[segs[s].down]
+				}
+			};
+			key(*a).total_cmp(&key(*b))
+		});
+		let mut p = columns[i].iter().copied();
+		for s in out {
+			bottom[s] = p.next().unwrap_or(left[i]);
+			if segs[s].looped {
+				bottom2[s] = p.next().unwrap_or(left[i]);
+			}
+		}
+		let mut inc: Vec<usize> = (0..segs.len())
+			.filter(|s| segs[*s].down == i && !segs[*s].looped)
+			.collect();
+		inc.sort_by(|a, b| pos[segs[*a].up].total_cmp(&pos[segs[*b].up]));
+		for (s, x) in inc.into_iter().zip(columns[i].iter().copied()) {
+			top[s] = x;
+		}
+	}
+
+	// rows: a band per layer as tall as its tallest box, and under it a row
+	// for arrowheads, a channel per segment (with a row for its label and a
+	// blank one after if it has one) and another row for arrowheads
+	let height = |i: usize| match items[i] {
+		Item::Node(_) => label_lines(i).len().max(1) + 2,
+		Item::Dummy => 1,
+	};
+	let mut band = vec![(0, 0); depth];
+	let mut channel = vec![0; segs.len()];
+	let mut row = 0;
+	for (l, layer) in layers.iter().enumerate() {
+		band[l] = (row, layer.iter().map(|i| height(*i)).max().unwrap_or(1));
+		row += band[l].1 + 1;
+		let mut here: Vec<usize> = (0..segs.len())
+			.filter(|s| layer_of[segs[*s].up] == l)
+			.collect();
+		here.sort_by_key(|s| bottom[*s]);
+		for s in here {
+			channel[s] = row;
+			let first = segs[s].looped || matches!(items[segs[s].up], Item::Node(_));
+			row += if first && g.edges[segs[s].edge].label.is_some() {
+				3 + gap
+			} else {
+				1
+			};
+		}
+		row += 1;
+	}
+	let top_row = |i: usize| band[layer_of[i]].0;
+	let bottom_row = |i: usize| match items[i] {
+		Item::Node(_) => band[layer_of[i]].0 + height(i) - 1,
+		// a dummy runs the whole height of its layer
+		Item::Dummy => band[layer_of[i]].0 + band[layer_of[i]].1 - 1,
+	};
+
+	let mut lines = Lines(vec![]);
+	for i in 0..items.len() {
+		let (l, r, t, b) = (left[i], right[i], top_row(i), bottom_row(i));
+		if items[i] == Item::Dummy {
+			for row in t..=b {
+				lines.set(Point::from((row, l)), '|');
+			}
+			continue;
+		}
+		for c in l..=r {
+			lines.set(Point::from((t, c)), '-');
+			lines.set(Point::from((b, c)), '-');
+		}
+		for row in t..=b {
+			lines.set(Point::from((row, l)), '|');
+			lines.set(Point::from((row, r)), '|');
+		}
+		lines.set(Point::from((t, l)), ',');
+		lines.set(Point::from((t, r)), '.');
+		lines.set(Point::from((b, l)), '\'');
+		lines.set(Point::from((b, r)), '\'');
+		for (row, text) in label_lines(i).iter().enumerate() {
+			for (j, c) in text.chars().enumerate() {
+				lines.set(Point::from((t + 1 + row, l + 2 + j)), c);
+			}
+		}
+	}
+
+	// horizontals first so the verticals cross over them, corners last
+	let ends = |s: usize| {
+		let a = bottom[s];
+		let b = if segs[s].looped { bottom2[s] } else { top[s] };
+		(a, b)
+	};
+	for (s, row) in channel.iter().enumerate() {
+		let (a, b) = ends(s);
+		for c in a.min(b)..=a.max(b) {
+			lines.set(Point::from((*row, c)), '-');
+		}
+	}
+	for (s, seg) in segs.iter().enumerate() {
+		let (a, b) = ends(s);
+		for row in bottom_row(seg.up) + 1..channel[s] {
+			lines.set(Point::from((row, a)), '|');
+			if seg.looped {
+				lines.set(Point::from((row, b)), '|');
+			}
+		}
+		if !seg.looped {
+			for row in channel[s] + 1..top_row(seg.down) {
+				lines.set(Point::from((row, b)), '|');
+			}
+		}
+	}
+	for (s, seg) in segs.iter().enumerate() {
+		use Direction::*;
+		let (a, b) = ends(s);
+		let (towards_b, towards_a) = if b > a { (Rt, Lt) } else { (Lt, Rt) };
+		if a == b {
+			lines.set(Point::from((channel[s], a)), '|');
+			continue;
+		}
+		lines.set(Point::from((channel[s], a)), corner(Up, towards_b));
+		let c = if seg.looped {
+			corner(Up, towards_a)
+		} else {
+			corner(towards_a, Dn)
+		};
+		lines.set(Point::from((channel[s], b)), c);
+	}
+
+	// arrowheads where the edges had them, on whichever of its hops touches
+	// that end's box
+	for (s, seg) in segs.iter().enumerate() {
+		let e = &g.edges[seg.edge];
+		let (a, b) = ends(s);
+		let at = |i: usize| match items[i] {
+			Item::Node(v) => Some(v),
+			Item::Dummy => None,
+		};
+		let arrow = |v: usize| (v == e.to && e.head) || (v == e.from && e.tail);
+		if seg.looped {
+			if e.tail {
+				lines.set(Point::from((bottom_row(seg.up) + 1, a)), '^');
+			}
+			if e.head {
+				lines.set(Point::from((bottom_row(seg.up) + 1, b)), '^');
+			}
+			continue;
+		}
+		if at(seg.up).is_some_and(arrow) {
+			lines.set(Point::from((bottom_row(seg.up) + 1, a)), '^');
+		}
+		if at(seg.down).is_some_and(arrow) {
+			lines.set(Point::from((top_row(seg.down) - 1, b)), 'v');
+		}
+	}
+
+	// every cell each edge was drawn over
+	let mut owners: HashMap<Point, Vec<usize>> = HashMap::new();
+	let mut own = |row: usize, col: usize, e: usize| {
+		owners.entry(Point { row, col }).or_default().push(e);
+	};
+	for (s, seg) in segs.iter().enumerate() {
+		let (a, b) = ends(s);
+		for c in a.min(b)..=a.max(b) {
+			own(channel[s], c, seg.edge);
+		}
+		for row in bottom_row(seg.up) + 1..channel[s] {
+			own(row, a, seg.edge);
+			if seg.looped {
+				own(row, b, seg.edge);
+			}
+		}
+		if !seg.looped {
+			for row in channel[s] + 1..=bottom_row(seg.down) {
+				if items[seg.down] == Item::Dummy || row < top_row(seg.down) {
+					own(row, b, seg.edge);
+				}
+			}
+		}
+	}
+
+	// labels go next to their edge, as near the start as they can while only
+	// touching that edge, or the parser would share them out
+	for (e, edge) in g.edges.iter().enumerate() {
+		let label = match &edge.label {
+			Some(l) => l.replace('\n', " "),
+			None => continue,
+		};
+		let n = label.chars().count();
+		let first = (0..segs.len()).find(|s| segs[*s].edge == e)?;
+		let (a, b) = ends(first);
+		let start = a.min(b) + 1;
+		let mut at: Vec<(usize, usize)> = owners
+			.iter()
+			.filter(|(_, es)| es.contains(&e))
+			.flat_map(|(p, _)| {
+				[p.row.saturating_sub(1), p.row, p.row + 1]
+					.into_iter()
+					.flat_map(move |r| (p.col.saturating_sub(n)..=p.col + 1).map(move |c| (r, c)))
+			})
+			.collect();
+		at.sort_by_key(|(r, c)| (r.abs_diff(channel[first] + 1) + c.abs_diff(start), *r, *c));
+		at.dedup();
+		let cell = |r: usize, c: usize| lines.at(Point { row: r, col: c }).unwrap_or(' ');
+		let fits = |r: usize, c: usize| {
+			let mut touches = false;
+			let window = (r.saturating_sub(1)..=r + 1)
+				.flat_map(|rr| (c.saturating_sub(1)..=c + n).map(move |cc| (rr, cc)));
+			for (rr, cc) in window {
+				let inside = rr == r && cc >= c && cc < c + n;
+				match owners.get(&Point { row: rr, col: cc }) {
+					Some(es) if es.iter().all(|o| *o == e) && !inside => touches = true,
+					Some(_) => return false,
+					None if cell(rr, cc) != ' ' => return false,
+					None => {}
+				}
+			}
+			touches
+		};
+		let (r, c) = at.into_iter().find(|(r, c)| fits(*r, *c))?;
+		for (j, ch) in label.chars().enumerate() {
+			lines.set(Point::from((r, c + j)), ch);
+		}
+	}
+	Some(lines)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::import;
+
+	// laying out then parsing gives back the same edges, by label
+	fn round_trip(g: &Graph) -> Lines {
+		let lines = layout(g).unwrap();
+		let dia = Diagram::parse(&lines);
+		let named = |g: &Graph| -> Vec<_> {
+			g.edges
+				.iter()
+				.map(|e| {
+					let name = |n: usize| g.nodes[n].label.clone();
+					(name(e.from), name(e.to), e.head, e.tail, e.label.clone())
+				})
+				.collect()
+		};
+		let mut got = named(&Graph::from(&dia));
+		let mut want = named(g);
+		// an edge that isn't one way can come back from either end
+		for es in [&mut got, &mut want] {
+			for e in es.iter_mut() {
+				if e.2 == e.3 && e.1 < e.0 || !e.2 && e.3 {
+					*e = (e.1.clone(), e.0.clone(), e.3, e.2, e.4.clone());
+				}
+			}
+			es.sort();
+		}
+		assert_eq!(want, got);
+		assert_eq!(g.nodes.len(), dia.boxes.len());
+		lines
+	}
+
+	#[test]
+	fn test_layers() {
+		let g = import::dot("digraph { a -> b; a -> c; b -> d; c -> d; a -> d }").unwrap();
+		let lines = round_trip(&g);
+		let dia = Diagram::parse(&lines);
+		let row = |name: &str| {
+			let id = (0..dia.boxes.len()).find(|i| dia.name(*i) == name);
+			dia.boxes[id.unwrap()].0.row
+		};
+		assert!(row("a") < row("b"));
+		assert_eq!(row("b"), row("c"));
+		assert!(row("c") < row("d"));
+	}
+
+	#[test]
+	fn test_cycles_and_loops() {
+		let g = import::dot(
+			r#"digraph {
+	a -> b [label="next"];
+	b -> c -> a;
+	c -> c [label="again"];
+	b -> a [dir=both];
+	d -- a;
+}"#,
+		)
+		.unwrap();
+		round_trip(&g);
+	}
+
+	#[test]
+	fn test_parallel_labels() {
+		// too many labels between the same two boxes for the tight layout
+		let g = import::dot(
+			r#"digraph {
+	node0 -> node1 [label="l10"];
+	node0 -> node1 [label="l11"];
+	node0 -> node1 [label="l12"];
+	node1 -> node0 [label="l13"];
+	node0 -> node1 [label="l14"];
+	node0 -> node1 [label="l15"];
+}"#,
+		)
+		.unwrap();
+		assert!(spaced(&g, 0).is_none());
+		round_trip(&g);
+	}
+
+	#[test]
+	fn test_relayout() {
+		let lines = Lines(
+			r#"
+ ,---.   ,-----.        ,---.
+ | a |<--| two |------->| b |
+ '---'   | rows|   yes  '---'
+         '-----'
+"#
+			.lines()
+			.map(|l| l.chars().collect())
+			.collect(),
+		);
+		let g = Graph::from(&Diagram::parse(&lines));
+		assert_eq!(2, g.edges.len());
+		round_trip(&g);
+	}
+
+	#[test]
+	fn test_left_out() {
+		let lines = |s: &str| Lines(s.lines().map(|l| l.chars().collect()).collect());
+		let check = |s: &str| left_out(&Diagram::parse(&lines(s)), &lines(s));
+		assert_eq!(
+			None,
+			check(
+				r#"
+ ,---.   ,-----.        ,---.
+ | a |<--| two |------->| b |
+ '---'   | rows|   yes  '---'
+         '-----'
+"#
+			)
+		);
+		assert_eq!(
+			Some("dangling edges"),
+			check(
+				r#"
+ ,---.
+ | a |---->
+ '---'
+"#
+			)
+		);
+		assert_eq!(
+			Some("boxes inside boxes"),
+			check(
+				r#"
+ ,---------.
+ | ,---.   |
+ | | a |   |
+ | '---'   |
+ '---------'
+"#
+			)
+		);
+		assert_eq!(
+			Some("free text"),
+			check(
+				r#"
+ ,---.     ,---.
+ | a |---->| b |
+ '---'     '---'
+   a note
+"#
+			)
+		);
+	}
+
+	#[test]
+	fn test_glyph_labels() {
+		// labels beside a vertical line, right under the corner it turns at
+		for l in ["-", "v", "a|b"] {
+			let g = import::dot(&format!(r#"digraph {{ a -> b [label="{}"]; }}"#, l)).unwrap();
+			round_trip(&g);
+		}
+	}
+}
@@ -1,35 +1,231 @@
-use std::collections::HashSet;
-use std::io::prelude::*;
+mod diagram;
+mod edit;
+mod export;
+mod file;
+mod history;
+mod import;
+mod layout;
 mod parse;
+mod route;
+use diagram::{Diagram, EdgeId};
+use file::File;
+use history::History;
 use parse::*;
 
 use crossterm::{
     cursor,
-    event::{self, read, Event, KeyCode, KeyEvent, KeyModifiers},
+    event::{read, Event, KeyCode, KeyEvent, KeyModifiers},
     style::{self, style, Color},
     terminal,
     tty::IsTty,
     ExecutableCommand, QueueableCommand,
 };
-use std::io::{BufRead, Write};
+use std::io::Write;
 
 fn main() {
-    let stdin = std::io::stdin();
-    let stdin = stdin.lock();
-    let buf: Vec<Vec<char>> = stdin
-        .lines()
-        .map(|l| l.unwrap().chars().collect())
-        .collect();
-    let lines = Lines(buf);
-    let bs = boxes(&lines);
-    let es = edges(&lines, &bs);
-    // println!("BOXES {:#?}", bs);
-    // println!("EDGES {:#?}", es);
-
-    let dres = ct_draw(&lines, es, bs);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("export") {
+        if let Err(e) = export(&args[1..]) {
+            eprintln!("graphedit: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if args.first().map(String::as_str) == Some("import") {
+        if let Err(e) = import(&args[1..]) {
+            eprintln!("graphedit: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if args.first().map(String::as_str) == Some("parse") {
+        if let Err(e) = parse_json(&args[1..]) {
+            eprintln!("graphedit: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if args.first().map(String::as_str) == Some("cat") || !std::io::stdout().is_tty() {
+        let args = match args.first().map(String::as_str) {
+            Some("cat") => &args[1..],
+            _ => &args[..],
+        };
+        if let Err(e) = cat(args) {
+            eprintln!("graphedit: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    let (file, lines) = match args.first() {
+        Some(path) => File::open(path.into()),
+        None => File::read(std::io::stdin().lock(), None),
+    }
+    .unwrap_or_else(|e| {
+        eprintln!("graphedit: {}", e);
+        std::process::exit(1);
+    });
+    // println!("BOXES {:#?}", boxes(&lines));
+    // println!("EDGES {:#?}", edges(&lines, &boxes(&lines)));
+
+    let dres = ct_draw(file, lines);
     ct_rst().and(dres).expect("term issues");
 }
 
+// graphedit export --format dot|mermaid|svg|html [--cell WxH] [--font NAME]
+//     [--font-size N] [file]
+fn export(args: &[String]) -> std::io::Result<()> {
+    let usage = || {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "usage: graphedit export --format dot|mermaid|svg|html [--cell WxH] [--font NAME] [--font-size N] [file]",
+        )
+    };
+    let mut format = None;
+    let mut path = None;
+    let mut style = export::SvgStyle::default();
+    let mut args = args.iter();
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            "--format" => format = Some(args.next().ok_or_else(usage)?.as_str()),
+            "--cell" => {
+                let (w, h) = args
+                    .next()
+                    .and_then(|c| c.split_once('x'))
+                    .ok_or_else(usage)?;
+                style.cell = (
+                    w.parse().map_err(|_| usage())?,
+                    h.parse().map_err(|_| usage())?,
+                );
+            }
+            "--font" => style.font = args.next().ok_or_else(usage)?.clone(),
+            "--font-size" => {
+                style.font_size = args.next().and_then(|n| n.parse().ok()).ok_or_else(usage)?
+            }
+            _ if a.starts_with('-') || path.is_some() => return Err(usage()),
+            _ => path = Some(a),
+        }
+    }
+    let (_, lines) = match path {
+        Some(path) => File::read(std::fs::File::open(path)?, Some(path.into()))?,
+        None => File::read(std::io::stdin().lock(), None)?,
+    };
+    let dia = Diagram::parse(&lines);
+    let out = std::io::stdout();
+    match format {
+        Some("dot") => export::dot(&dia, out.lock()),
+        Some("mermaid") => export::mermaid(&dia, out.lock()),
+        Some("svg") => export::svg(&dia, &lines, &style, out.lock()),
+        Some("html") => export::html(&dia, &lines, out.lock()),
+        Some(f) => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("unknown export format {:?}", f),
+        )),
+        None => Err(usage()),
+    }
+}
+
+// graphedit import --format dot|mermaid [file]
+fn import(args: &[String]) -> std::io::Result<()> {
+    use std::io::Read;
+    let usage = || {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "usage: graphedit import --format dot|mermaid [file]",
+        )
+    };
+    let mut format = None;
+    let mut path = None;
+    let mut args = args.iter();
+    while let Some(a) = args.next() {
+        match a.as_str() {
+            "--format" => format = Some(args.next().ok_or_else(usage)?.as_str()),
+            _ if a.starts_with('-') || path.is_some() => return Err(usage()),
+            _ => path = Some(a),
+        }
+    }
+    let src = match path {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => {
+            let mut s = String::new();
+            std::io::stdin().read_to_string(&mut s)?;
+            s
+        }
+    };
+    let graph = match format {
+        Some("dot") => import::dot(&src),
+        Some("mermaid") => import::mermaid(&src),
+        Some(f) => Err(format!("unknown import format {:?}", f)),
+        None => return Err(usage()),
+    }
+    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    File::new(None).write(&import::draw(&graph), std::io::stdout().lock())
+}
+
+// graphedit parse --json [file]
+fn parse_json(args: &[String]) -> std::io::Result<()> {
+    let usage = || {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "usage: graphedit parse --json [file]",
+        )
+    };
+    let mut as_json = false;
+    let mut path = None;
+    for a in args {
+        match a.as_str() {
+            "--json" => as_json = true,
+            _ if a.starts_with('-') || path.is_some() => return Err(usage()),
+            _ => path = Some(a),
+        }
+    }
+    if !as_json {
+        return Err(usage());
+    }
+    let (_, lines) = match path {
+        Some(path) => File::read(std::fs::File::open(path)?, Some(path.into()))?,
+        None => File::read(std::io::stdin().lock(), None)?,
+    };
+    export::json(&Diagram::parse(&lines), &lines, std::io::stdout().lock())
+}
+
+// graphedit cat [--color=always|never|auto] [--theme=NAME] [file]
+fn cat(args: &[String]) -> std::io::Result<()> {
+    let usage = || {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "usage: graphedit cat [--color=always|never|auto] [--theme=default|bright|mono] [file]",
+        )
+    };
+    let mut color = None;
+    let mut theme = Theme::DEFAULT;
+    let mut path = None;
+    for a in args {
+        match a.split_once('=') {
+            Some(("--color", "always")) => color = Some(true),
+            Some(("--color", "never")) => color = Some(false),
+            Some(("--color", "auto")) => color = None,
+            Some(("--theme", name)) => theme = Theme::named(name).ok_or_else(usage)?,
+            _ if a.starts_with('-') || path.is_some() => return Err(usage()),
+            _ => path = Some(a),
+        }
+    }
+    let (_, lines) = match path {
+        Some(path) => File::read(std::fs::File::open(path)?, Some(path.into()))?,
+        None => File::read(std::io::stdin().lock(), None)?,
+    };
+    let out = std::io::stdout();
+    let color = color.unwrap_or_else(|| out.is_tty() && std::env::var_os("NO_COLOR").is_none());
+    let mut out = out.lock();
+    if color {
+        simp_draw(&lines, &Diagram::parse(&lines), &theme, &mut out)
+    } else {
+        for line in &lines.0 {
+            writeln!(out, "{}", line.iter().collect::<String>())?;
+        }
+        Ok(())
+    }
+}
+
 fn ct_rst() -> crossterm::Result<()> {
     terminal::disable_raw_mode().and(
         std::io::stdout()
@@ -50,20 +246,39 @@ macro_rules! keyevt {
     };
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Normal,
+    // hjkl drags the focused box around instead of the cursor
+    Move,
+    // hjkl moves the focused box's bottom right corner, HJKL its top left
+    Resize,
+    // typed characters overwrite whatever is under the cursor
+    Text,
+    // hjkl looks for the box a new edge goes to, e or enter draws it
+    Edge,
+    // y or n for each edge of the box being deleted, whether it goes too
+    Delete,
+}
+
+// everything undo needs to put the editor back how it was
+struct Snapshot {
+    lines: Lines,
+    cursor: (u16, u16),
+    view: (usize, usize),
+    focus: Option<TBox>,
+}
+
 fn move_inside(inner: TBox, outer: TBox, d: Direction) -> Option<TBox> {
     let border = border_in_dir(outer, d);
     if !inner.intersects(border) {
         return inner.in_dir(d);
     }
-    return None;
+    None
 }
 
-fn ct_draw(lines: &Lines, es: HashSet<Vec<Point>>, bs: Vec<TBox>) -> crossterm::Result<()> {
+fn ct_draw(mut file: File, mut lines: Lines) -> crossterm::Result<()> {
     let mut stdout = std::io::stdout();
-    if !stdout.is_tty() {
-        stdout.execute(style::Print("not a tty"))?;
-        return Ok(());
-    }
     stdout.execute(terminal::EnterAlternateScreen)?;
     terminal::enable_raw_mode()?;
 
@@ -107,34 +322,59 @@ fn ct_draw(lines: &Lines, es: HashSet<Vec<Point>>, bs: Vec<TBox>) -> crossterm::
     //   lt (-1, 0) => if pc > 0 { 1. } elif vc > 0 { 2. } else 3.
 
     let mut move_debug = String::new();
-    let mut focus : Option<TBox> = None;
+    let mut msg = String::new();
+    // the file name being typed in for save-as
+    let mut prompt: Option<String> = None;
+    let mut focus: Option<TBox> = None;
+    let mut mode = Mode::Normal;
+    // the box a new edge starts from, and how it would look drawn to the focus
+    let mut edge_from: Option<TBox> = None;
+    let mut preview: Option<(Lines, Vec<Point>)> = None;
+    // the box being deleted, its edges still to ask about and the ones going
+    let mut deleting: Option<(BoxId, Vec<EdgeId>, Vec<EdgeId>)> = None;
+    let mut dia = Diagram::parse(&lines);
+    let mut history = History::new();
+    // the history generation last written out
+    let mut saved_at = history.generation();
+    // the buffer as of the last edit, so snapshots are only taken for edits
+    let mut unedited = lines.clone();
+    // edits made in the same move/resize/text session undo together
+    let mut grouped = false;
     loop {
+        let modified = history.generation() != saved_at;
         if dirty {
             stdout.queue(cursor::MoveTo(0, 0))?;
             stdout.queue(terminal::Clear(terminal::ClearType::All))?;
-            let cursor = Point {
-                row: pr as usize + vr,
-                col: pc as usize + vc,
-            };
-            for row in 0..min(rows as usize, lines.0.len()) {
-                for col in 0..min(cols as usize, lines.0[vr + row].len()) {
+            let shown = preview.as_ref().map_or(&lines, |(l, _)| l);
+            let new_edge = preview.as_ref().map(|(_, pth)| pth);
+            let asking = deleting
+                .as_ref()
+                .and_then(|(_, ask, _)| ask.first())
+                .map(|i| &dia.edges[*i].path);
+            for row in 0..min(rows as usize, shown.0.len().saturating_sub(vr)) {
+                for col in 0..min(cols as usize, shown.0[vr + row].len().saturating_sub(vc)) {
                     let p = Point {
                         row: vr + row,
                         col: vc + col,
                     };
-                    if es.iter().any(|e| path_contains(e, p)) {
+                    if asking.is_some_and(|pth| path_contains(pth, p)) {
+                        stdout
+                            .queue(style::PrintStyledContent(style(shown[p]).with(Color::Red)))?;
+                    } else if dia.edges.iter().any(|e| path_contains(&e.path, p))
+                        || new_edge.is_some_and(|pth| path_contains(pth, p))
+                    {
                         stdout.queue(style::PrintStyledContent(
-                            style(lines[p]).with(Color::Yellow),
+                            style(shown[p]).with(Color::Yellow),
                         ))?;
-                    } else if focus.iter().any(|b| b.contains(p)) {
-                        stdout
-                            .queue(style::PrintStyledContent(style(lines[p]).with(Color::White)))?;
-                    } else if bs.iter().any(|b| b.contains(p)) {
+                    } else if focus.iter().chain(&edge_from).any(|b| b.contains(p)) {
                         stdout.queue(style::PrintStyledContent(
-                            style(lines[p]).with(Color::Blue),
+                            style(shown[p]).with(Color::White),
                         ))?;
+                    } else if dia.box_at(p).is_some() {
+                        stdout
+                            .queue(style::PrintStyledContent(style(shown[p]).with(Color::Blue)))?;
                     } else {
-                        stdout.queue(style::Print(lines[p]))?;
+                        stdout.queue(style::Print(shown[p]))?;
                     }
                 }
                 stdout.queue(cursor::MoveToNextLine(1))?;
@@ -144,24 +384,275 @@ fn ct_draw(lines: &Lines, es: HashSet<Vec<Point>>, bs: Vec<TBox>) -> crossterm::
             dirty = false;
         }
 
+        let name = file
+            .path
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "[stdin]".to_string());
+        let status = match &prompt {
+            Some(input) => format!("save as: {}", input),
+            None => format!(
+                "{}{} {:?} {} {} p: {:?} v: {:?} {:?} | {}{}",
+                name,
+                if modified { " [+]" } else { "" },
+                mode,
+                vr + pr as usize,
+                vc + pc as usize,
+                (pr, pc),
+                (vr, vc),
+                (rows, cols),
+                msg,
+                move_debug
+            ),
+        };
         stdout
             .queue(cursor::MoveTo(0, rows))?
-            .queue(style::PrintStyledContent(
-                style(format!(
-                    "{} {} p: {:?} v: {:?} {:?} | {}",
-                    vr + pr as usize,
-                    vc + pc as usize,
-                    (pr, pc),
-                    (vr, vc),
-                    (rows, cols),
-                    move_debug
-                ))
-                .on(Color::DarkBlue),
-            ))?
+            .queue(terminal::Clear(terminal::ClearType::CurrentLine))?
+            .queue(style::PrintStyledContent(style(status).on(Color::DarkBlue)))?
             .queue(cursor::MoveTo(pc, pr))?
             .flush()?;
 
-        let mut move_cursor = |d: Direction| {
+        let evt = read()?;
+        if let Some(input) = prompt.as_mut() {
+            match evt {
+                Event::Key(KeyEvent {
+                    code: KeyCode::Char(c),
+                    ..
+                }) => input.push(c),
+                Event::Key(KeyEvent {
+                    code: KeyCode::Backspace,
+                    ..
+                }) => {
+                    input.pop();
+                }
+                Event::Key(KeyEvent {
+                    code: KeyCode::Enter,
+                    ..
+                }) => {
+                    if !input.is_empty() {
+                        let res = file.save_as(&lines, input.as_str().into());
+                        msg = saved(res, &lines, &mut saved_at, history.generation());
+                    }
+                    prompt = None;
+                }
+                Event::Key(KeyEvent {
+                    code: KeyCode::Esc, ..
+                }) => prompt = None,
+                _ => {}
+            }
+            continue;
+        }
+
+        let at = Point::from((pr as usize + vr, pc as usize + vc));
+        let before = ((pr, pc), (vr, vc), focus);
+        let mode_before = mode;
+        let mut edited = false;
+        let mut insert = None;
+        let mut undo = None;
+        let mut shifted = false;
+        msg.clear();
+        let dir = match evt {
+            Event::Key(KeyEvent {
+                code: KeyCode::Char(c),
+                modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT,
+            }) if mode == Mode::Text => {
+                lines.set(at, c);
+                dia = Diagram::parse(&lines);
+                edited = true;
+                dirty = true;
+                Some(Direction::Rt)
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Backspace,
+                ..
+            }) if mode == Mode::Text => {
+                if let Some(p) = at.in_dir(Direction::Lt).filter(|p| lines.at(*p).is_some()) {
+                    lines.set(p, ' ');
+                    dia = Diagram::parse(&lines);
+                    edited = true;
+                    dirty = true;
+                }
+                Some(Direction::Lt)
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char(c @ ('y' | 'n')),
+                ..
+            }) if mode == Mode::Delete => {
+                if let Some((id, ask, drop)) = deleting.as_mut() {
+                    let e = ask.remove(0);
+                    if c == 'y' {
+                        drop.push(e);
+                    }
+                    if ask.is_empty() {
+                        edit::delete_box(&mut lines, &dia, *id, drop);
+                        dia = Diagram::parse(&lines);
+                        edited = true;
+                        mode = Mode::Normal;
+                    }
+                    dirty = true;
+                }
+                None
+            }
+            // nothing moves and q doesn't quit while the edges are being asked about
+            Event::Key(KeyEvent {
+                code: KeyCode::Char(_),
+                modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT,
+            }) if mode == Mode::Delete => None,
+            keyevt!('q') | keyevt!('c', CONTROL) if modified => {
+                msg = "unsaved changes, w to write them or Q to quit anyway".to_string();
+                None
+            }
+            keyevt!('q') | keyevt!('c', CONTROL) | keyevt!('Q', SHIFT) => return Ok(()),
+            keyevt!('w') if mode == Mode::Normal && file.path.is_some() => {
+                let res = file.save(&lines);
+                msg = saved(res, &lines, &mut saved_at, history.generation());
+                None
+            }
+            keyevt!('w') | keyevt!('W', SHIFT) if mode == Mode::Normal => {
+                prompt = Some(String::new());
+                None
+            }
+            keyevt!('t') if mode == Mode::Normal => {
+                mode = Mode::Text;
+                None
+            }
+            _ if motion(mode, &evt).is_some() => motion(mode, &evt).map(|(d, s)| {
+                shifted = s;
+                d
+            }),
+            keyevt!('m') if mode == Mode::Normal && focus.is_some() => {
+                mode = Mode::Move;
+                None
+            }
+            keyevt!('r') if mode == Mode::Normal && focus.is_some() => {
+                mode = Mode::Resize;
+                None
+            }
+            keyevt!('m')
+            | keyevt!('r')
+            | Event::Key(KeyEvent {
+                code: KeyCode::Esc, ..
+            }) => {
+                mode = Mode::Normal;
+                None
+            }
+            keyevt!('i') if mode == Mode::Normal => {
+                insert = Some(Direction::Rt);
+                None
+            }
+            keyevt!('I', SHIFT) if mode == Mode::Normal => {
+                insert = Some(Direction::Dn);
+                None
+            }
+            keyevt!('e') if mode == Mode::Normal && focus.is_some() => {
+                mode = Mode::Edge;
+                edge_from = focus;
+                msg = "move to another box and e to draw an edge to it".to_string();
+                None
+            }
+            keyevt!('e')
+            | Event::Key(KeyEvent {
+                code: KeyCode::Enter,
+                ..
+            }) if mode == Mode::Edge => {
+                if let Some((l, _)) = preview.take() {
+                    lines = l;
+                    dia = Diagram::parse(&lines);
+                    edited = true;
+                    dirty = true;
+                }
+                mode = Mode::Normal;
+                None
+            }
+            keyevt!('d') if mode == Mode::Normal => {
+                if let Some(id) = focus.and_then(|b| dia.boxes.iter().position(|o| *o == b)) {
+                    let ask: Vec<EdgeId> = dia.attached(id).collect();
+                    if dia.children(id).next().is_some() {
+                        msg = "delete the boxes inside it first".to_string();
+                    } else if ask.is_empty() {
+                        edit::delete_box(&mut lines, &dia, id, &[]);
+                        dia = Diagram::parse(&lines);
+                        edited = true;
+                    } else {
+                        mode = Mode::Delete;
+                        deleting = Some((id, ask, vec![]));
+                    }
+                    dirty = true;
+                }
+                None
+            }
+            keyevt!('a') if mode == Mode::Normal => {
+                // laying out would lose anything that isn't a box, an edge
+                // between two boxes or a label
+                let laid = match layout::left_out(&dia, &lines) {
+                    Some(what) => Err(format!("can't lay out {}, they'd be lost", what)),
+                    None => layout::layout(&import::Graph::from(&dia)),
+                };
+                match laid {
+                    Ok(laid) => {
+                        lines = laid;
+                        dia = Diagram::parse(&lines);
+                        (vr, vc) = (0, 0);
+                        edited = true;
+                    }
+                    Err(e) => msg = e,
+                }
+                dirty = true;
+                None
+            }
+            keyevt!('u') if mode == Mode::Normal => {
+                undo = Some(false);
+                None
+            }
+            keyevt!('r', CONTROL) if mode == Mode::Normal => {
+                undo = Some(true);
+                None
+            }
+            Event::Resize(c, r) => {
+                cols = c;
+                rows = r;
+                pr = min(rows, pr);
+                pc = min(cols, pc);
+                dirty = true;
+                None
+            }
+            _ => None,
+        };
+        if let Some(d) = insert {
+            let p = Point::from((pr as usize + vr, pc as usize + vc));
+            if edit::insert(&mut lines, p, (3, 4), d).is_some() {
+                edited = true;
+                dia = Diagram::parse(&lines);
+                dirty = true;
+            }
+        }
+        if let Some(redo) = undo {
+            let current = Snapshot {
+                lines: lines.clone(),
+                cursor: (pr, pc),
+                view: (vr, vc),
+                focus,
+            };
+            let restored = if redo {
+                history.redo(current)
+            } else {
+                history.undo(current)
+            };
+            match restored {
+                Some(s) => {
+                    lines = s.lines;
+                    (pr, pc) = (min(rows, s.cursor.0), min(cols, s.cursor.1));
+                    (vr, vc) = s.view;
+                    focus = s.focus;
+                    dia = Diagram::parse(&lines);
+                    unedited = lines.clone();
+                    dirty = true;
+                }
+                None if redo => msg = "nothing to redo".to_string(),
+                None => msg = "nothing to undo".to_string(),
+            }
+        }
+        let mut move_cursor = |lines: &Lines, d: Direction| {
             move_debug = String::new();
             let p = Point::from((pr as usize, pc as usize));
             let pbox = TBox::from((p, p));
@@ -179,12 +670,14 @@ fn ct_draw(lines: &Lines, es: HashSet<Vec<Point>>, bs: Vec<TBox>) -> crossterm::
                 Point::from((vr, vc)),
                 Point::from((vr + rows as usize - 1, vr + cols as usize - 1)),
             ));
-            let windowed_max = lines.0[vr..(vr + rows as usize)]
+            // the buffer can be shorter than the screen, or empty
+            let end = min(vr + rows as usize, lines.0.len());
+            let windowed_max = lines.0[min(vr, end)..end]
                 .iter()
                 .fold(0, |lmax, l| max(lmax, l.len()));
             let lbox = TBox::from((
                 Point::from((0, 0)),
-                Point::from((lines.0.len() - 1, windowed_max)),
+                Point::from((lines.0.len().saturating_sub(1), windowed_max)),
             ));
             move_debug = format!("{} | {:?} {:?} {:?}", move_debug, pbox, vbox, lbox);
             if let Some(b) = move_inside(vbox, lbox, d) {
@@ -193,54 +686,193 @@ fn ct_draw(lines: &Lines, es: HashSet<Vec<Point>>, bs: Vec<TBox>) -> crossterm::
                 dirty = true;
             }
         };
-        match read()? {
-            keyevt!('q') | keyevt!('c', CONTROL) => return Ok(()),
-            keyevt!('j') => move_cursor(Direction::Dn),
-            keyevt!('k') => move_cursor(Direction::Up),
-            keyevt!('l') => move_cursor(Direction::Rt),
-            keyevt!('h') => move_cursor(Direction::Lt),
-            Event::Resize(c, r) => {
-                cols = c;
-                rows = r;
-                pr = min(rows, pr);
-                pc = min(cols, pc);
-                dirty = true;
+        match (mode, dir, focus) {
+            (Mode::Move, Some(d), Some(b)) => {
+                let moved = dia
+                    .boxes
+                    .iter()
+                    .position(|o| *o == b)
+                    .and_then(|id| edit::translate(&mut lines, &dia, id, d));
+                if moved.is_some() {
+                    edited = true;
+                    move_cursor(&lines, d);
+                    dia = Diagram::parse(&lines);
+                    dirty = true;
+                }
+            }
+            (Mode::Resize, Some(d), Some(b)) => {
+                use Direction::*;
+                let (side, grow) = match (shifted, d) {
+                    (false, Rt | Dn) | (true, Lt | Up) => (d, true),
+                    _ => (d.rev(), false),
+                };
+                if let Some(nb) = edit::resize(&mut lines, b, side, grow) {
+                    edited = true;
+                    dia = Diagram::parse(&lines);
+                    focus = Some(nb);
+                    dirty = true;
+                }
             }
+            (_, Some(d), _) if !shifted => move_cursor(&lines, d),
             _ => {}
         }
 
-        let nextfocus = bs.iter().find(|b1| b1.contains(Point::from((pr as usize + vr, pc as usize + vc)))).map(|b| *b);
-        dirty |= nextfocus != focus;
-        focus = nextfocus;
+        if edited {
+            let prev = std::mem::replace(&mut unedited, lines.clone());
+            if !grouped {
+                history.record(Snapshot {
+                    lines: prev,
+                    cursor: before.0,
+                    view: before.1,
+                    focus: before.2,
+                });
+            }
+            grouped = mode != Mode::Normal;
+        }
+        if mode != mode_before {
+            grouped = false;
+        }
+
+        // the cursor may end up outside a box while it's being resized
+        if mode != Mode::Resize {
+            let nextfocus = dia
+                .box_at(Point::from((pr as usize + vr, pc as usize + vc)))
+                .map(|i| dia.boxes[i]);
+            dirty |= nextfocus != focus;
+            focus = nextfocus;
+        }
+        // routed again on every move, so what enter draws is what's on screen
+        let next = match (mode, edge_from, focus) {
+            (Mode::Edge, Some(a), Some(b)) if a != b => {
+                let mut l = lines.clone();
+                let pth = route::connect(&mut l, &dia.boxes, a, b, true);
+                if pth.is_none() {
+                    msg = "no way through for that edge".to_string();
+                }
+                pth.map(|pth| (l, pth))
+            }
+            _ => None,
+        };
+        dirty |= next.as_ref().map(|n| &n.1) != preview.as_ref().map(|p| &p.1);
+        preview = next;
+        if mode != Mode::Edge {
+            dirty |= edge_from.take().is_some();
+        }
+        match &deleting {
+            Some((_, ask, _)) if mode == Mode::Delete => {
+                msg = format!("delete the red edge too? y/n ({} left)", ask.len())
+            }
+            _ => dirty |= deleting.take().is_some(),
+        }
+    }
+}
+
+// the way hjkl moves the cursor, or the focused box in move mode, and
+// whether it was shifted, text and delete mode keep the keys to themselves
+fn motion(mode: Mode, evt: &Event) -> Option<(Direction, bool)> {
+    use Direction::*;
+    match evt {
+        _ if matches!(mode, Mode::Text | Mode::Delete) => None,
+        keyevt!('j') => Some((Dn, false)),
+        keyevt!('k') => Some((Up, false)),
+        keyevt!('l') => Some((Rt, false)),
+        keyevt!('h') => Some((Lt, false)),
+        Event::Key(KeyEvent {
+            code: KeyCode::Char(c @ ('J' | 'K' | 'L' | 'H')),
+            ..
+        }) => match c {
+            'J' => Some((Dn, true)),
+            'K' => Some((Up, true)),
+            'L' => Some((Rt, true)),
+            _ => Some((Lt, true)),
+        },
+        _ => None,
+    }
+}
+
+// the status line message for how writing the buffer out went
+fn saved(res: std::io::Result<()>, lines: &Lines, saved_at: &mut usize, gen: usize) -> String {
+    match res {
+        Ok(()) => {
+            *saved_at = gen;
+            format!("wrote {} lines", lines.0.len())
+        }
+        Err(e) => format!("couldn't write: {}", e),
     }
 }
 
-fn set_style<'s>(prev: &'s str, next: &'s str) -> &'s str {
-    if prev == next {
-        return prev;
+// the SGR codes simp_draw colours each part of a diagram with
+struct Theme {
+    edge: &'static str,
+    boxes: &'static str,
+    text: &'static str,
+}
+
+impl Theme {
+    const DEFAULT: Theme = Theme {
+        edge: "\x1b[33m",
+        boxes: "\x1b[34m",
+        text: "\x1b[0m",
+    };
+
+    fn named(name: &str) -> Option<Theme> {
+        match name {
+            "default" => Some(Theme::DEFAULT),
+            "bright" => Some(Theme {
+                edge: "\x1b[93m",
+                boxes: "\x1b[94m",
+                text: "\x1b[0m",
+            }),
+            // for terminals, or people, that would rather not have colour
+            "mono" => Some(Theme {
+                edge: "\x1b[1m",
+                boxes: "\x1b[2m",
+                text: "\x1b[0m",
+            }),
+            _ => None,
+        }
     }
-    print!("{}", next);
-    next
 }
 
-fn simp_draw(lines: &Lines, es: HashSet<Vec<Point>>, bs: Vec<TBox>) {
+// an attribute like bold stays on through a later dim or colour, so go back
+// to plain before switching from one style to another
+fn set_style<'s>(prev: &'s str, next: &'s str, w: &mut impl Write) -> std::io::Result<&'s str> {
+    let rst = "\x1b[0m";
+    if prev != next {
+        if prev != rst && next != rst {
+            write!(w, "{}", rst)?;
+        }
+        write!(w, "{}", next)?;
+    }
+    Ok(next)
+}
+
+// only writes an SGR code when the style actually changes, and goes back to
+// plain at the end of every line so pagers can start anywhere
+fn simp_draw(
+    lines: &Lines,
+    dia: &Diagram,
+    theme: &Theme,
+    w: &mut impl Write,
+) -> std::io::Result<()> {
     let rst = "\x1b[0m";
     let mut style = rst;
     for r in 0..lines.0.len() {
         for c in 0..lines.0[r].len() {
             let p = Point::from((r, c));
-            if es.iter().any(|e| path_contains(e, p)) {
-                style = set_style(style, "\x1b[33m");
-            } else if bs.iter().any(|b| b.contains(p)) {
-                style = set_style(style, "\x1b[34m");
-            } else {
-                style = set_style(style, rst);
+            if dia.edges.iter().any(|e| path_contains(&e.path, p)) {
+                style = set_style(style, theme.edge, w)?;
+            } else if dia.box_at(p).is_some() {
+                style = set_style(style, theme.boxes, w)?;
+            } else if !lines.0[r][c].is_whitespace() {
+                style = set_style(style, theme.text, w)?;
             }
-            print!("{}", lines.0[r][c]);
+            write!(w, "{}", lines.0[r][c])?;
         }
-        println!();
+        style = set_style(style, rst, w)?;
+        writeln!(w)?;
     }
-    set_style(style, rst);
+    Ok(())
 }
 
 #[cfg(test)]
@@ -264,4 +896,48 @@ mod test {
         let pbox = move_inside(pbox, vbox, Direction::Dn).unwrap();
         assert_eq!(move_inside(pbox, vbox, Direction::Dn), None);
     }
+
+    #[test]
+    fn test_motion() {
+        let j = keyevt!('j');
+        let shift_h = keyevt!('H', SHIFT);
+        assert_eq!(Some((Direction::Dn, false)), motion(Mode::Normal, &j));
+        assert_eq!(Some((Direction::Lt, true)), motion(Mode::Resize, &shift_h));
+        // answering y/n about the edges of a box being deleted, the cursor
+        // stays put
+        assert_eq!(None, motion(Mode::Delete, &j));
+        assert_eq!(None, motion(Mode::Delete, &shift_h));
+        assert_eq!(None, motion(Mode::Text, &j));
+    }
+
+    #[test]
+    fn test_simp_draw() {
+        let lines = Lines(
+            [",-.   ,-.", "| |-->| |", "'-' x '-'"]
+                .iter()
+                .map(|l| l.chars().collect())
+                .collect(),
+        );
+        let mut out = vec![];
+        simp_draw(&lines, &Diagram::parse(&lines), &Theme::DEFAULT, &mut out).unwrap();
+        assert_eq!(
+            concat!(
+                "\x1b[34m,-.   ,-.\x1b[0m\n",
+                "\x1b[34m| |\x1b[0m\x1b[33m-->\x1b[0m\x1b[34m| |\x1b[0m\n",
+                "\x1b[34m'-' \x1b[0mx \x1b[34m'-'\x1b[0m\n",
+            ),
+            String::from_utf8(out).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_set_style() {
+        let mono = Theme::named("mono").unwrap();
+        let mut out = vec![];
+        let style = set_style("\x1b[0m", mono.edge, &mut out).unwrap();
+        let style = set_style(style, mono.edge, &mut out).unwrap();
+        // bold is reset rather than left on under the dim
+        set_style(style, mono.boxes, &mut out).unwrap();
+        assert_eq!("\x1b[1m\x1b[0m\x1b[2m", String::from_utf8(out).unwrap());
+    }
 }
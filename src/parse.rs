@@ -9,8 +9,70 @@ pub struct Point {
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct TBox(pub Point, pub Point);
 
+#[derive(Clone)]
 pub struct Lines(pub Vec<Vec<char>>);
 
+// index of a box in the list boxes() returns
+pub type BoxId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Arrow {
+	pub glyph: char,
+	pub dir: Direction,
+}
+
+// some text from the drawing, trimmed, a line per row with where it starts
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Label(pub Vec<(Point, String)>);
+
+impl Label {
+	pub fn text(&self) -> String {
+		self.0
+			.iter()
+			.map(|(_, s)| s.as_str())
+			.collect::<Vec<_>>()
+			.join("\n")
+	}
+
+	// all on one line, for referring to a box by
+	pub fn name(&self) -> String {
+		self.0
+			.iter()
+			.map(|(_, s)| s.as_str())
+			.collect::<Vec<_>>()
+			.join(" ")
+	}
+}
+
+// text next to an edge, with how sure we are it belongs to that edge, 1 when
+// it's the only edge that close and less when others are just as close. a
+// label made of several runs gets the least sure of them, so one shared run
+// brings the whole label down
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeLabel {
+	pub text: Label,
+	pub confidence: f32,
+}
+
+// a line from one box to another, with path running from the from end to the
+// to end, either end can be left dangling
+//
+//  ,-.        ,-.  head only, from -> to
+//  | |------->| |  head and tail, both ways
+//  '-'        '-'  neither, undirected and from/to are arbitrary
+//
+// a lone arrowhead always ends up as the head, so a dangling end is only ever
+// a from if the arrow points at a box
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edge {
+	pub from: Option<BoxId>,
+	pub to: Option<BoxId>,
+	pub path: Vec<Point>,
+	pub head: Option<Arrow>,
+	pub tail: Option<Arrow>,
+	pub label: Option<EdgeLabel>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Direction {
 	Up,
@@ -20,10 +82,10 @@ pub enum Direction {
 }
 
 impl Direction {
-	const VALUES: [Direction; 4] = [Direction::Up, Direction::Dn, Direction::Lt, Direction::Rt];
+	pub const VALUES: [Direction; 4] = [Direction::Up, Direction::Dn, Direction::Lt, Direction::Rt];
 
 	#[inline]
-	fn rev(self) -> Direction {
+	pub fn rev(self) -> Direction {
 		use Direction::*;
 		match self {
 			Up => Dn,
@@ -32,18 +94,81 @@ impl Direction {
 			Rt => Lt,
 		}
 	}
+
+	#[inline]
+	pub fn delta(self) -> (isize, isize) {
+		use Direction::*;
+		match self {
+			Up => (-1, 0),
+			Dn => (1, 0),
+			Lt => (0, -1),
+			Rt => (0, 1),
+		}
+	}
 }
 
+// light, heavy, double and rounded box drawing characters (and their dashed
+// variants) by which way they connect
+//
+//  ┌─┬─┐ ┏━┳━┓ ╔═╦═╗ ╭─╮
+//  ├─┼─┤ ┣━╋━┫ ╠═╬═╣ │ │
+//  └─┴─┘ ┗━┻━┛ ╚═╩═╝ ╰─╯
+//
 #[inline]
-fn can_go(c: char, d: Direction) -> bool {
+pub fn can_go(c: char, d: Direction) -> bool {
 	use Direction::*;
-	match (c, d) {
-		('|', Up | Dn) | ('-', Lt | Rt) => true,
-		('.', Dn | Lt) => true,
-		(',', Dn | Rt) => true,
-		('\'', Up | Lt | Rt) => true,
-		('<', Rt) | ('>', Lt) | ('^', Up) | ('v', Dn) => true,
-		_ => false,
+	matches!(
+		(c, d),
+		('|', Up | Dn)
+			| ('-', Lt | Rt)
+			| ('.', Dn | Lt)
+			| (',', Dn | Rt)
+			| ('\'', Up | Lt | Rt)
+			| ('<', Rt)
+			| ('>', Lt)
+			| ('^', Dn)
+			| ('v', Up)
+			| ('│' | '┃' | '║' | '┆' | '┇' | '┊' | '┋' | '╎' | '╏', Up | Dn)
+			| ('─' | '━' | '═' | '┄' | '┅' | '┈' | '┉' | '╌' | '╍', Lt | Rt)
+			| ('┌'..='┏' | '╒'..='╔' | '╭', Dn | Rt)
+			| ('┐'..='┓' | '╕'..='╗' | '╮', Dn | Lt)
+			| ('└'..='┗' | '╘'..='╚' | '╰', Up | Rt)
+			| ('┘'..='┛' | '╛'..='╝' | '╯', Up | Lt)
+			| ('├'..='┣' | '╞'..='╠', Up | Dn | Rt)
+			| ('┤'..='┫' | '╡'..='╣', Up | Dn | Lt)
+			| ('┬'..='┳' | '╤'..='╦', Dn | Lt | Rt)
+			| ('┴'..='┻' | '╧'..='╩', Up | Lt | Rt)
+			| ('┼'..='╋' | '╪'..='╬', _)
+	)
+}
+
+// which way an arrowhead points, its line comes from the other side
+//
+//  --->  <---  ^  |
+//              |  v
+pub fn arrow_dir(c: char) -> Option<Direction> {
+	use Direction::*;
+	match c {
+		'^' => Some(Up),
+		'v' => Some(Dn),
+		'<' => Some(Lt),
+		'>' => Some(Rt),
+		_ => None,
+	}
+}
+
+// the glyph that joins a path coming from direction a and leaving towards b
+//   Up+Rt  Dn+Lt  Dn+Rt
+//    |        -.    ,-
+//    '-        |    |
+pub fn corner(a: Direction, b: Direction) -> char {
+	use Direction::*;
+	match (a, b) {
+		(Up | Dn, Up | Dn) => '|',
+		(Lt | Rt, Lt | Rt) => '-',
+		(Up, _) | (_, Up) => '\'',
+		(Dn, Lt) | (Lt, Dn) => '.',
+		_ => ',',
 	}
 }
 
@@ -62,7 +187,7 @@ impl std::fmt::Debug for Point {
 
 impl Point {
 	#[inline]
-	fn in_dir(self, d: Direction) -> Option<Point> {
+	pub fn in_dir(self, d: Direction) -> Option<Point> {
 		use Direction::*;
 		match d {
 			Dn => Some(Point::from((self.row + 1, self.col))),
@@ -83,6 +208,26 @@ impl Point {
 			}
 		}
 	}
+
+	#[inline]
+	pub fn offset(self, dr: isize, dc: isize) -> Option<Point> {
+		let row = self.row.checked_add_signed(dr)?;
+		let col = self.col.checked_add_signed(dc)?;
+		Some(Point { row, col })
+	}
+
+	// direction of q from self, if they share a row or column
+	pub fn dir_to(self, q: Point) -> Option<Direction> {
+		use std::cmp::Ordering::*;
+		use Direction::*;
+		match (q.row.cmp(&self.row), q.col.cmp(&self.col)) {
+			(Less, Equal) => Some(Up),
+			(Greater, Equal) => Some(Dn),
+			(Equal, Less) => Some(Lt),
+			(Equal, Greater) => Some(Rt),
+			_ => None,
+		}
+	}
 }
 
 impl From<(Point, Point)> for TBox {
@@ -105,10 +250,18 @@ impl std::fmt::Debug for TBox {
 impl TBox {
 	#[inline]
 	pub fn contains(&self, p: Point) -> bool {
-		["hey", "there"].into_iter().flat_map(|s| s.chars());
 		p.row >= self.0.row && p.row <= self.1.row && p.col >= self.0.col && p.col <= self.1.col
 	}
 
+	#[inline]
+	pub fn on_border(&self, p: Point) -> bool {
+		self.contains(p)
+			&& (p.row == self.0.row
+				|| p.row == self.1.row
+				|| p.col == self.0.col
+				|| p.col == self.1.col)
+	}
+
 	#[inline]
 	pub fn intersects(&self, b: TBox) -> bool {
 		!(self.1.row < b.0.row
@@ -118,19 +271,14 @@ impl TBox {
 	}
 
 	#[inline]
-	fn points<'p>(self) -> impl Iterator<Item = Point> + 'p {
-		let col_iter = move |row| {
-			(self.0.col..=self.1.col)
-				.into_iter()
-				.map(move |col| Point { row, col })
-		};
-		(self.0.row..=self.1.row).into_iter().flat_map(col_iter)
+	pub fn points<'p>(self) -> impl Iterator<Item = Point> + 'p {
+		let col_iter = move |row| (self.0.col..=self.1.col).map(move |col| Point { row, col });
+		(self.0.row..=self.1.row).flat_map(col_iter)
 	}
 
 	#[inline]
 	pub fn in_dir(&self, d: Direction) -> Option<TBox> {
-		self
-			.0
+		self.0
 			.in_dir(d)
 			.and_then(|p0| self.1.in_dir(d).map(|p1| TBox(p0, p1)))
 	}
@@ -149,27 +297,84 @@ impl std::ops::IndexMut<Point> for Lines {
 }
 
 impl Lines {
-	fn at(&self, p: Point) -> Option<char> {
-		if p.row as usize >= self.0.len() {
-			return None;
+	pub fn at(&self, p: Point) -> Option<char> {
+		self.0.get(p.row).and_then(|line| line.get(p.col)).copied()
+	}
+
+	// write c at p, padding with spaces if the buffer doesn't reach that far
+	pub fn set(&mut self, p: Point, c: char) {
+		if self.0.len() <= p.row {
+			self.0.resize_with(p.row + 1, Vec::new);
 		}
-		let line = &self.0[p.row as usize];
-		if p.col as usize >= line.len() {
-			return None;
+		let line = &mut self.0[p.row];
+		if line.len() <= p.col {
+			line.resize(p.col + 1, ' ');
 		}
-		Some(line[p.col as usize])
+		line[p.col] = c;
 	}
 
 	fn in_dir(&self, p: Point, d: Direction) -> Option<(Point, char)> {
 		p.in_dir(d).and_then(|p| self.at(p).map(|c| (p, c)))
 	}
 
-	fn visit(&self, mut pred: impl FnMut(Point, char)) {
-		for r in 0..self.0.len() {
-			for c in 0..self.0[r].len() {
-				pred((r, c).into(), self.0[r][c]);
+	// can_go for the character at p, except a '+' is a corner or junction
+	// that connects to whatever lines are next to it
+	//
+	//  +--+   +--
+	//  |  |---+
+	//  +--+   |
+	pub fn connects(&self, p: Point, d: Direction) -> bool {
+		match self.at(p) {
+			Some('+') => self
+				.in_dir(p, d)
+				.map(|(_, c)| c == '+' || can_go(c, d.rev()))
+				.unwrap_or(false),
+			Some(c) => can_go(c, d),
+			None => false,
+		}
+	}
+
+	// a line heading d goes straight over the character at p rather than
+	// joining it, as long as the line carries on the other side
+	//
+	//    |      |      |
+	//  --+--  --|--  --)--
+	//    |      |      |
+	fn crossing(&self, p: Point, d: Direction) -> bool {
+		use Direction::*;
+		let (l, r) = match d {
+			Up | Dn => (Lt, Rt),
+			Lt | Rt => (Up, Dn),
+		};
+		let across = match self.at(p) {
+			// explicit hops over the other line
+			Some(')' | '~') => true,
+			Some('+') => Direction::VALUES.into_iter().all(|d| self.connects(p, d)),
+			// a line running across, not a corner like ' that one turns at
+			Some(c) => {
+				Direction::VALUES.into_iter().all(|d| can_go(c, d))
+					|| (can_go(c, l) && can_go(c, r) && !can_go(c, d) && !can_go(c, d.rev()))
+			}
+			None => false,
+		};
+		across
+			&& self
+				.in_dir(p, d)
+				.map(|(q, _)| self.connects(q, d.rev()))
+				.unwrap_or(false)
+	}
+
+	// every point after p going in d, for as long as the line carries on
+	fn run(&self, mut p: Point, d: Direction) -> Vec<Point> {
+		let mut ret = vec![];
+		while let Some((q, _)) = self.in_dir(p, d) {
+			if !self.connects(p, d) || !self.connects(q, d.rev()) || ret.contains(&q) {
+				break;
 			}
+			ret.push(q);
+			p = q;
 		}
+		ret
 	}
 }
 
@@ -179,15 +384,15 @@ fn top_lefts(lines: &Lines) -> Vec<(Point, char)> {
 		for col in 0..lines.0[row].len() {
 			let c = lines.0[row][col];
 			let p = Point { row, col };
-			if can_go(c, Direction::Dn)
-				&& can_go(c, Direction::Rt)
+			if lines.connects(p, Direction::Dn)
+				&& lines.connects(p, Direction::Rt)
 				&& lines
 					.in_dir(p, Direction::Rt)
-					.map(|(_, c)| can_go(c, Direction::Lt))
+					.map(|(q, _)| lines.connects(q, Direction::Lt))
 					.unwrap_or(false)
 				&& lines
 					.in_dir(p, Direction::Dn)
-					.map(|(_, c)| can_go(c, Direction::Up))
+					.map(|(q, _)| lines.connects(q, Direction::Up))
 					.unwrap_or(false)
 			{
 				ret.push((p, c));
@@ -197,131 +402,92 @@ fn top_lefts(lines: &Lines) -> Vec<(Point, char)> {
 	ret
 }
 
-fn scan_dir(lines: &Lines, mut p: Point, d: Direction) -> Option<(Point, char)> {
-	while let Some((q, c)) = lines.in_dir(p, d) {
-		//  p
-		// --* < can't connect
-		//
-		if !can_go(c, d.rev()) {
-			return lines.at(p).map(|c| (p, c));
-		}
-		p = q;
-		//   p
-		// --. < can connect, can't continue
-		//
-		if !can_go(c, d) {
-			return Some((p, c));
-		}
-	}
-	lines.at(p).map(|c| (p, c))
-}
-
-struct PathIter<'l> {
-	start: bool,
-	lines: &'l Lines,
-	p: Point,
-	d: Direction,
-}
-
-impl<'l> PathIter<'l> {
-	fn new(lines: &'l Lines, p: Point, d: Direction) -> PathIter<'l> {
-		PathIter {
-			start: true,
-			lines: lines,
-			p: p,
-			d: d,
-		}
-	}
-}
-
-//       * 4
-//   1 2 |
-//  |----' 3
+// follow a line from p (heading d to start with), splitting off at every
+// junction, and return the corners of one path per end reached, a line stops
+// short of any box border it runs into
 //
-// 1. start, returns point, begins path-scan
-// 2. edge, while current can send, and next can recv, advance cursor
-// 3. turn, return point, find next direction (if you can)
-// 4. end, current can't send or next can't recv, return final point (if not already returned)
-// 5. exit, same as end, but signal end of iteration
+//      * 4
+//      |
+//  *---'---* 3
+//  1   2
 //
-
+// 1. start, walk cell by cell, trying straight on before turning
+// 2. junction, more than one way to carry on, follow each in turn
+// 3. end, can't carry on (or would walk into a box), that's one path
+// 4. same again for every other branch
 //
-// * > point and direction
-//
-// 0. test if point exists
-// 1. test if you can go that direction
-// 2. if so, scan in that direction (returns last point *after* initial, character)
-//    2a. mark last point as path point
-// 3. if not, pick a direction you haven't tried, go back to 1.
-impl<'l> Iterator for PathIter<'l> {
-	type Item = Point;
-	fn next(&mut self) -> Option<Self::Item> {
-		if self.lines.at(self.p).is_none() {
-			return None;
-		} else if self.start {
-			self.start = false;
-			return Some(self.p);
-		}
-
-		let mut cant_go = vec![self.d.rev()];
-		loop {
-			// println!("PathIter {{ p: {:?}, d: {:?} }}", self.p, self.d);
-			if let (Some(true), Some(true)) = (
-				self.lines.at(self.p).map(|c| can_go(c, self.d)),
-				self
-					.lines
-					.in_dir(self.p, self.d)
-					.map(|(_, c)| can_go(c, self.d.rev())),
-			) {
-				if let Some((pnext, c)) = scan_dir(self.lines, self.p, self.d) {
-					// println!("scan_dir = Some(({:?}, {:?}))", pnext, c);
-					self.p = pnext;
-					return Some(pnext);
-				}
-			}
-
-			cant_go.push(self.d);
-			if let Some(dnext) = Direction::VALUES
-				.into_iter()
-				.filter(|d| !cant_go.contains(d))
-				.next()
-			{
-				self.d = dnext;
-				continue;
-			} else {
-				return None;
-			}
-		}
-	}
+fn trace(lines: &Lines, boxes: &[TBox], p: Point, d: Direction) -> Vec<Vec<Point>> {
+	let mut ret = vec![];
+	walk(lines, boxes, d, &mut vec![p], &mut ret);
+	ret.into_iter().map(|cells| path_corners(&cells)).collect()
 }
 
-fn scan_path(lines: &Lines, p: Point, d: Direction) -> Vec<Point> {
-	if !lines.at(p).map(|c| can_go(c, d)).unwrap_or(false) {
-		return vec![];
-	}
-	let mut ret = vec![];
-	let mut it = PathIter::new(&lines, p, d);
-	while let Some(next) = it.next() {
-		if ret.contains(&next) {
-			return ret;
+fn walk(
+	lines: &Lines,
+	boxes: &[TBox],
+	d: Direction,
+	cells: &mut Vec<Point>,
+	ret: &mut Vec<Vec<Point>>,
+) {
+	let p = cells[cells.len() - 1];
+	// crossing another line, the only way on is straight ahead
+	let straight = cells.len() > 1 && lines.crossing(p, d);
+	let mut ended = true;
+	for n in std::iter::once(d).chain(
+		Direction::VALUES
+			.into_iter()
+			.filter(|n| *n != d && *n != d.rev()),
+	) {
+		let q = match p.in_dir(n) {
+			Some(q) => q,
+			None => continue,
+		};
+		if (straight && n != d)
+			|| !(straight || lines.connects(p, n))
+			|| !(lines.connects(q, n.rev()) || lines.crossing(q, n))
+			|| cells.contains(&q)
+			|| (boxes.iter().any(|b| b.on_border(q)) && !lines.crossing(q, n))
+		{
+			continue;
 		}
-		ret.push(next);
+		ended = false;
+		cells.push(q);
+		walk(lines, boxes, n, cells, ret);
+		cells.pop();
+	}
+	if ended {
+		ret.push(cells.clone());
 	}
-	ret
 }
 
+// from each top left, look for the nearest top right and bottom left that
+// close a rectangle, so a line carrying on past a corner doesn't hide it
+//
+//  +--+---->
+//  |  |
+//  +--+
 pub fn boxes(lines: &Lines) -> Vec<TBox> {
+	use Direction::*;
 	top_lefts(lines)
 		.into_iter()
-		.filter_map(|tl| {
-			let tr = scan_dir(lines, tl.0, Direction::Rt)?;
-			let bl = scan_dir(lines, tl.0, Direction::Dn)?;
-			let br = scan_dir(lines, bl.0, Direction::Rt)?;
-			let br2 = scan_dir(lines, tr.0, Direction::Dn)?;
-			if br2 != br {
-				return None;
-			}
-			Some(TBox(tl.0, br.0))
+		.filter_map(|(tl, _)| {
+			let left = lines.run(tl, Dn);
+			lines
+				.run(tl, Rt)
+				.into_iter()
+				.filter(|tr| lines.connects(*tr, Dn))
+				.find_map(|tr| {
+					let right = lines.run(tr, Dn);
+					left.iter()
+						.filter(|bl| lines.connects(**bl, Rt))
+						.map(|bl| (*bl, Point::from((bl.row, tr.col))))
+						.find(|(bl, br)| {
+							right.contains(br)
+								&& lines.connects(*br, Lt)
+								&& lines.run(*bl, Rt).contains(br)
+						})
+						.map(|(_, br)| TBox(tl, br))
+				})
 		})
 		.collect()
 }
@@ -336,7 +502,7 @@ pub fn border_in_dir(b: TBox, d: Direction) -> TBox {
 	}
 }
 
-fn border(b: TBox) -> Vec<(Point, Direction)> {
+pub fn border(b: TBox) -> Vec<(Point, Direction)> {
 	Direction::VALUES
 		.into_iter()
 		// get eg top border
@@ -348,17 +514,16 @@ fn border(b: TBox) -> Vec<(Point, Direction)> {
 		.collect()
 }
 
-pub fn path_contains(pth: &Vec<Point>, p: Point) -> bool {
+pub fn path_contains(pth: &[Point], p: Point) -> bool {
 	let mut it = pth.iter();
-	let fst = it.next();
-	if !fst.is_some() {
-		return false;
-	}
-	let mut last = fst.unwrap();
+	let mut last = match it.next() {
+		Some(fst) => fst,
+		None => return false,
+	};
 	if *last == p {
 		return true;
 	}
-	while let Some(next) = it.next() {
+	for next in it {
 		if TBox::from((*last, *next)).contains(p) {
 			return true;
 		}
@@ -367,28 +532,206 @@ pub fn path_contains(pth: &Vec<Point>, p: Point) -> bool {
 	false
 }
 
-pub fn edges(lines: &Lines, boxes: &Vec<TBox>) -> HashSet<Vec<Point>> {
+// every cell a path passes over, in order
+pub fn path_points(pth: &[Point]) -> Vec<Point> {
+	let mut ret: Vec<Point> = pth.iter().take(1).copied().collect();
+	for w in pth.windows(2) {
+		let mut p = w[0];
+		while let Some(q) = p.dir_to(w[1]).and_then(|d| p.in_dir(d)) {
+			ret.push(q);
+			p = q;
+		}
+	}
+	ret
+}
+
+// the corners of a path given every cell it passes over, the inverse of path_points
+pub fn path_corners(cells: &[Point]) -> Vec<Point> {
+	let mut ret: Vec<Point> = vec![];
+	for (i, p) in cells.iter().enumerate() {
+		let turns = match (ret.last(), cells.get(i + 1)) {
+			(Some(prev), Some(next)) => prev.dir_to(*p) != p.dir_to(*next),
+			_ => true,
+		};
+		if turns {
+			ret.push(*p);
+		}
+	}
+	ret
+}
+
+// the line at p points towards d, either carrying on or as an arrowhead
+fn touches(lines: &Lines, p: Point, d: Direction) -> bool {
+	lines.connects(p, d) || lines.at(p).and_then(arrow_dir) == Some(d)
+}
+
+// the arrowhead at p, if there is one pointing out of the line towards d
+fn arrow_at(lines: &Lines, p: Point, d: Direction) -> Option<Arrow> {
+	let glyph = lines.at(p)?;
+	(arrow_dir(glyph)? == d).then_some(Arrow { glyph, dir: d })
+}
+
+// the box (and the way to it) that the end of a line at p runs into, having
+// got there heading d
+fn end_box(lines: &Lines, boxes: &[TBox], p: Point, d: Direction) -> (Option<BoxId>, Direction) {
+	std::iter::once(d)
+		.chain(
+			Direction::VALUES
+				.into_iter()
+				.filter(|o| *o != d && *o != d.rev()),
+		)
+		.filter(|o| touches(lines, p, *o))
+		.find_map(|o| {
+			let q = p.in_dir(o)?;
+			boxes
+				.iter()
+				.position(|b| b.on_border(q))
+				.map(|i| (Some(i), o))
+		})
+		.unwrap_or((None, d))
+}
+
+pub fn edges(lines: &Lines, boxes: &[TBox]) -> Vec<Edge> {
 	//   ###
 	//  ,---. ##
 	// #|   |,--.  find all possible starts for edges between boxes
 	//  '---''--'
 	//   ###  ##
-	boxes
-		.iter()
-		.map(|b| border(*b))
-		.flat_map(|v| v.into_iter())
-		.filter(|(p, d)| lines.at(*p).map(|c| can_go(c, d.rev())).unwrap_or(false))
-		.map(|(p, d)| scan_path(lines, p, d))
-		.filter(|pth| pth.len() > 0)
-		.fold(HashSet::new(), |mut map, mut pth| {
-			// checking the forward path then inserting
-			// the reverse means we don't double-count paths
-			if !map.contains(&pth) {
-				pth.reverse();
-				map.insert(pth);
+	//
+	// a line that splits at a junction gives one edge per pair of ends
+	let mut seen = HashSet::new();
+	let mut ret = vec![];
+	for (i, b) in boxes.iter().enumerate() {
+		for (p, d) in border(*b) {
+			// a line running across the border is picked up from whatever it reaches
+			let across = p
+				.in_dir(d.rev())
+				.map(|q| lines.crossing(q, d.rev()))
+				.unwrap_or(false);
+			if !touches(lines, p, d.rev()) || across || boxes.iter().any(|b| b.on_border(p)) {
+				continue;
 			}
-			map
-		})
+			for mut path in trace(lines, boxes, p, d) {
+				// a line between two boxes is found from both, keep the first
+				let rev: Vec<Point> = path.iter().rev().copied().collect();
+				if seen.contains(&path) || seen.contains(&rev) {
+					continue;
+				}
+				seen.insert(path.clone());
+
+				let lst = path[path.len() - 1];
+				let arrived = match path.len() {
+					1 => d,
+					n => path[n - 2].dir_to(lst).unwrap_or(d),
+				};
+				let (mut to, out) = end_box(lines, boxes, lst, arrived);
+				let mut from = Some(i);
+				let mut tail = arrow_at(lines, p, d.rev());
+				let mut head = arrow_at(lines, lst, out);
+				if head.is_none() && tail.is_some() {
+					std::mem::swap(&mut from, &mut to);
+					std::mem::swap(&mut head, &mut tail);
+					path.reverse();
+				}
+				ret.push(Edge {
+					from,
+					to,
+					path,
+					head,
+					tail,
+					label: None,
+				});
+			}
+		}
+	}
+
+	//  ,-.  yes   ,-.
+	//  | |------->| |  text touching a path (even diagonally) labels it, text
+	//  | | maybe  | |  touching more than one is shared out between them
+	//  | |------->| |
+	//  '-'        '-'
+	let runs = text_runs(lines, boxes, |p| {
+		ret.iter().any(|e| path_contains(&e.path, p))
+	});
+	for (at, text) in runs {
+		let near: Vec<usize> = (0..ret.len())
+			.filter(|i| {
+				(0..text.chars().count())
+					.map(|n| Point::from((at.row, at.col + n)))
+					.flat_map(|p| {
+						(-1..=1).flat_map(move |dr| (-1..=1).filter_map(move |dc| p.offset(dr, dc)))
+					})
+					.any(|q| path_contains(&ret[*i].path, q))
+			})
+			.collect();
+		let confidence = 1.0 / near.len() as f32;
+		for i in near {
+			let l = ret[i].label.get_or_insert(EdgeLabel {
+				text: Label::default(),
+				confidence,
+			});
+			l.text.0.push((at, text.clone()));
+			l.confidence = l.confidence.min(confidence);
+		}
+	}
+	ret
+}
+
+// runs of text outside any box and not part of a line, words separated by a
+// single space stay in the same run, but a line or a box always ends one
+//
+//  yes|no  two runs, either side of the line
+pub(crate) fn text_runs(
+	lines: &Lines,
+	boxes: &[TBox],
+	taken: impl Fn(Point) -> bool,
+) -> Vec<(Point, String)> {
+	let mut ret = vec![];
+	for (row, l) in lines.0.iter().enumerate() {
+		let mut run: Option<(Point, String)> = None;
+		let mut gap = false;
+		for col in 0..=l.len() {
+			let p = Point { row, col };
+			let blocked = taken(p) || boxes.iter().any(|b| b.contains(p));
+			let c = lines.at(p).filter(|c| !c.is_whitespace() && !blocked);
+			match (c, &mut run) {
+				(Some(c), Some((_, s))) => {
+					if gap {
+						s.push(' ');
+					}
+					s.push(c);
+					gap = false;
+				}
+				(Some(c), None) => run = Some((p, c.to_string())),
+				(None, Some(_)) if !gap && !blocked && col < l.len() => gap = true,
+				(None, Some(_)) => {
+					ret.extend(run.take());
+					gap = false;
+				}
+				(None, None) => {}
+			}
+		}
+	}
+	// a bit of line carrying on from an edge, or joined up into a line of its
+	// own, isn't text, but a lone glyph off to the side of one (a v, a -) can be
+	ret.retain(|(at, s)| {
+		let glyphs = s
+			.chars()
+			.all(|c| c == ' ' || c == '+' || Direction::VALUES.into_iter().any(|d| can_go(c, d)));
+		let cells: Vec<Point> = (0..s.chars().count())
+			.map(|n| Point::from((at.row, at.col + n)))
+			.collect();
+		// only if the line it points at points back, a v under a - is still text
+		let joined =
+			|q: Point, d: Direction| (taken(q) || cells.contains(&q)) && lines.connects(q, d.rev());
+		let line = cells.iter().any(|p| {
+			Direction::VALUES
+				.into_iter()
+				.any(|d| lines.connects(*p, d) && p.in_dir(d).is_some_and(|q| joined(q, d)))
+		});
+		!(glyphs && line)
+	});
+	ret
 }
 
 #[cfg(test)]
@@ -424,6 +767,13 @@ mod test {
 		);
 	}
 
+	// where a straight run from p heading d ends, or p itself if the line
+	// doesn't carry on that way
+	fn scan_dir(lines: &Lines, p: Point, d: Direction) -> Option<(Point, char)> {
+		let q = lines.run(p, d).last().copied().unwrap_or(p);
+		lines.at(q).map(|c| (q, c))
+	}
+
 	#[test]
 	fn test_scan_dir() {
 		let lines = lines();
@@ -476,19 +826,363 @@ mod test {
 			Point { row: 5, col: 10 },
 		];
 
-		assert_eq!(pth, scan_path(&lines, pth[0], Direction::Rt),);
+		assert_eq!(vec![pth.clone()], trace(&lines, &[], pth[0], Direction::Rt));
 		// should work in reverse
 		pth.reverse();
-		assert_eq!(pth, scan_path(&lines, pth[0], Direction::Rt),);
+		assert_eq!(vec![pth.clone()], trace(&lines, &[], pth[0], Direction::Rt));
 
 		// |--' |--'
 		//  ^     ^
 		// instead of the beginning, start a little aways
 		pth[0].col += 1;
-		assert_eq!(pth, scan_path(&lines, pth[0], Direction::Rt),);
+		assert_eq!(vec![pth.clone()], trace(&lines, &[], pth[0], Direction::Rt));
+	}
+
+	#[test]
+	fn test_unicode() {
+		let lines = Lines(
+			r#"
+ ┌───┐  ╔══╗
+ │   ├──╢  ║
+ └───┘  ╚══╝
+ ╭─╮ ┏━┓
+ ╰─╯ ┗┳┛
+      ┃
+"#
+			.lines()
+			.map(|l| l.chars().collect())
+			.collect(),
+		);
+		assert_eq!(
+			vec![
+				TBox(Point { row: 1, col: 1 }, Point { row: 3, col: 5 }),
+				TBox(Point { row: 1, col: 8 }, Point { row: 3, col: 11 }),
+				TBox(Point { row: 4, col: 1 }, Point { row: 5, col: 3 }),
+				TBox(Point { row: 4, col: 5 }, Point { row: 5, col: 7 }),
+			],
+			boxes(&lines)
+		);
+		let es: Vec<_> = edges(&lines, &boxes(&lines))
+			.into_iter()
+			.map(|e| e.path)
+			.collect();
+		assert_eq!(2, es.len());
+		assert!(
+			es.contains(&vec![Point { row: 2, col: 6 }, Point { row: 2, col: 7 }])
+				|| es.contains(&vec![Point { row: 2, col: 7 }, Point { row: 2, col: 6 }])
+		);
+	}
+
+	#[test]
+	fn test_plus() {
+		let lines = Lines(
+			r#"
+ +---+   +--+
+ | a |---+  |
+ +-+-+   +--+---->
+   |
+   +-----+
+      +--+--+
+      |     |
+      +-----+
+"#
+			.lines()
+			.map(|l| l.chars().collect())
+			.collect(),
+		);
+		assert_eq!(
+			vec![
+				TBox(Point { row: 1, col: 1 }, Point { row: 3, col: 5 }),
+				TBox(Point { row: 1, col: 9 }, Point { row: 3, col: 12 }),
+				TBox(Point { row: 6, col: 6 }, Point { row: 8, col: 12 }),
+			],
+			boxes(&lines)
+		);
+		let es: Vec<_> = edges(&lines, &boxes(&lines))
+			.into_iter()
+			.map(|e| e.path)
+			.collect();
+		assert_eq!(3, es.len());
+		let bend = vec![
+			Point { row: 4, col: 3 },
+			Point { row: 5, col: 3 },
+			Point { row: 5, col: 9 },
+		];
+		assert!(es
+			.iter()
+			.any(|e| *e == bend || e.iter().rev().eq(bend.iter())));
+	}
+
+	#[test]
+	fn test_corner() {
+		for a in Direction::VALUES {
+			for b in Direction::VALUES {
+				let c = corner(a, b);
+				assert!(can_go(c, a) && can_go(c, b), "{:?} {:?} {:?}", a, b, c);
+			}
+		}
+	}
+
+	#[test]
+	fn test_path_points() {
+		let pth = vec![
+			Point { row: 2, col: 6 },
+			Point { row: 1, col: 6 },
+			Point { row: 1, col: 8 },
+		];
+		assert_eq!(
+			vec![
+				Point { row: 2, col: 6 },
+				Point { row: 1, col: 6 },
+				Point { row: 1, col: 7 },
+				Point { row: 1, col: 8 },
+			],
+			path_points(&pth)
+		);
+	}
+
+	#[test]
+	fn test_path_corners() {
+		let pth = vec![
+			Point { row: 2, col: 6 },
+			Point { row: 1, col: 6 },
+			Point { row: 1, col: 8 },
+			Point { row: 3, col: 8 },
+		];
+		assert_eq!(pth, path_corners(&path_points(&pth)));
+	}
+
+	#[test]
+	fn test_fan_out() {
+		let lines = Lines(
+			r#"
+    ,--.
+    |  |
+    '--'
+     |
+ ,---'---+---.
+ |       |   |
+,--.    ,--.,--.
+'--'    '--''--'
+"#
+			.lines()
+			.map(|l| l.chars().collect())
+			.collect(),
+		);
+		let bs = boxes(&lines);
+		assert_eq!(4, bs.len());
+		let es: Vec<_> = edges(&lines, &bs).into_iter().map(|e| e.path).collect();
+		// every pair of ends on the bus
+		assert_eq!(6, es.len());
+		let a_to_b = vec![
+			Point { row: 4, col: 5 },
+			Point { row: 5, col: 5 },
+			Point { row: 5, col: 1 },
+			Point { row: 6, col: 1 },
+		];
+		assert!(es
+			.iter()
+			.any(|e| *e == a_to_b || e.iter().rev().eq(a_to_b.iter())));
+		let b_to_d = vec![
+			Point { row: 6, col: 1 },
+			Point { row: 5, col: 1 },
+			Point { row: 5, col: 13 },
+			Point { row: 6, col: 13 },
+		];
+		assert!(es
+			.iter()
+			.any(|e| *e == b_to_d || e.iter().rev().eq(b_to_d.iter())));
+	}
+
+	#[test]
+	fn test_crossing() {
+		for c in ['+', '┼', '|', '-', ')', '~'] {
+			let lines = Lines(
+				format!(
+					r#"
+   ,--.
+   |  |
+   '--'
+,--. |  ,--.
+|  |-{}--|  |
+'--' |  '--'
+    ,--.
+    '--'
+"#,
+					c
+				)
+				.lines()
+				.map(|l| l.chars().collect())
+				.collect(),
+			);
+			let bs = boxes(&lines);
+			assert_eq!(4, bs.len());
+			let mut es: Vec<_> = edges(&lines, &bs).into_iter().map(|e| e.path).collect();
+			for e in es.iter_mut() {
+				e.sort_by_key(|p| (p.row, p.col));
+			}
+			es.sort_by_key(|e| (e[0].row, e[0].col));
+			assert_eq!(
+				vec![
+					vec![Point { row: 4, col: 5 }, Point { row: 6, col: 5 }],
+					vec![Point { row: 5, col: 4 }, Point { row: 5, col: 7 }],
+				],
+				es,
+				"crossing with {:?}",
+				c
+			);
+		}
+	}
+
+	#[test]
+	fn test_direction() {
+		let lines = Lines(
+			r#"
+ ,-.    ,-.
+ | |--->| |
+ '-'    '-'
+ ,-.    ,-.
+ | |<---| |
+ '-'    '-'
+ ,-.    ,-.
+ | |<-->| |
+ '-'    '-'
+ ,-.    ,-.
+ | |----| |
+ '-'    '-'
+ ,-.
+ | |---->
+ '-'
+  |
+  v
+"#
+			.lines()
+			.map(|l| l.chars().collect())
+			.collect(),
+		);
+		let bs = boxes(&lines);
+		assert_eq!(9, bs.len());
+		let mut es: Vec<_> = edges(&lines, &bs)
+			.into_iter()
+			.map(|e| {
+				(
+					e.from,
+					e.to,
+					e.head.map(|a| a.glyph),
+					e.tail.map(|a| a.glyph),
+				)
+			})
+			.collect();
+		es.sort();
+		assert_eq!(
+			vec![
+				(Some(0), Some(1), Some('>'), None),
+				(Some(3), Some(2), Some('<'), None),
+				(Some(4), Some(5), Some('>'), Some('<')),
+				(Some(6), Some(7), None, None),
+				(Some(8), None, Some('>'), None),
+				(Some(8), None, Some('v'), None),
+			],
+			es
+		);
+		// the path runs from the from box to the to box
+		let e = edges(&lines, &bs)
+			.into_iter()
+			.find(|e| e.from == Some(3))
+			.unwrap();
+		assert_eq!(
+			vec![Point { row: 5, col: 7 }, Point { row: 5, col: 4 }],
+			e.path
+		);
+	}
+
+	#[test]
+	fn test_edge_labels() {
+		let lines = Lines(
+			r#"
+ ,-.  yes   ,-.
+ | |------->| |
+ | | both   | |
+ | |--------| |
+ '-'  <->   '-'
+"#
+			.lines()
+			.map(|l| l.chars().collect())
+			.collect(),
+		);
+		let bs = boxes(&lines);
+		let es = edges(&lines, &bs);
+		assert_eq!(2, es.len());
+		let top = es.iter().find(|e| e.head.is_some()).unwrap();
+		let bottom = es.iter().find(|e| e.head.is_none()).unwrap();
+		assert_eq!(
+			Some(EdgeLabel {
+				text: Label(vec![
+					(Point { row: 1, col: 6 }, "yes".to_string()),
+					(Point { row: 3, col: 5 }, "both".to_string()),
+				]),
+				confidence: 0.5,
+			}),
+			top.label
+		);
+		assert_eq!(
+			Some(EdgeLabel {
+				text: Label(vec![(Point { row: 3, col: 5 }, "both".to_string())]),
+				confidence: 0.5,
+			}),
+			bottom.label
+		);
+	}
+
+	#[test]
+	fn test_glyph_labels() {
+		let lines = Lines(
+			r#"
+ ,-.   v    ,-.
+ | |------->| |
+ '-'        '-'
+"#
+			.lines()
+			.map(|l| l.chars().collect())
+			.collect(),
+		);
+		let bs = boxes(&lines);
+		let es = edges(&lines, &bs);
+		assert_eq!(1, es.len());
+		assert_eq!(
+			Some(Label(vec![(Point { row: 1, col: 7 }, "v".to_string())])),
+			es[0].label.as_ref().map(|l| l.text.clone())
+		);
+	}
+
+	#[test]
+	fn test_labels_split_by_line() {
+		let lines = Lines(
+			r#"
+ ,---.
+ '---'
+ yes|no
+ ,---.
+ '---'
+"#
+			.lines()
+			.map(|l| l.chars().collect())
+			.collect(),
+		);
+		let bs = boxes(&lines);
+		let es = edges(&lines, &bs);
+		assert_eq!(1, es.len());
+		assert_eq!(
+			Some(Label(vec![
+				(Point { row: 3, col: 1 }, "yes".to_string()),
+				(Point { row: 3, col: 5 }, "no".to_string()),
+			])),
+			es[0].label.as_ref().map(|l| l.text.clone())
+		);
 	}
 
 	#[test]
+	#[allow(clippy::bool_assert_comparison)]
 	fn test_box_contains() {
 		let lb = TBox(Point { row: 1, col: 1 }, Point { row: 4, col: 5 });
 
@@ -0,0 +1,315 @@
+use crate::edit::draw_path;
+use crate::parse::*;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+// what a step costs on top of moving one cell
+const TURN: usize = 4;
+const CROSS: usize = 6;
+const CROWDED: usize = 1;
+
+// where the search is: a cell, the way it got there, and whether it has to
+// carry straight on (leaving a box or just after hopping over another line)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct State {
+	row: usize,
+	col: usize,
+	dir: u8,
+	straight: bool,
+}
+
+fn dir_index(d: Direction) -> u8 {
+	Direction::VALUES.iter().position(|o| *o == d).unwrap() as u8
+}
+
+// the cheapest orthogonal path (as corners) from any of starts to any of ends,
+// both given as border() gives them: the cell just outside a box and the way
+// out of it. the path never enters a box or anything already drawn, except to
+// hop straight over another line, and only turns out in the open. a box around
+// both ends (a container they share) is open space, but its border isn't
+//
+//  ,---.        ,---.        ,---.        ,---.
+//  | a |  ,--.  | b |        | a |  ,--.  | b |
+//  '---'  |c |  '---'   =>   '---'  |c |  '---'
+//         '--'                  |   '--'   ^
+//                               |          |
+//                               '----------'
+pub fn route(
+	lines: &Lines,
+	boxes: &[TBox],
+	starts: &[(Point, Direction)],
+	ends: &[(Point, Direction)],
+) -> Option<Vec<Point>> {
+	let rows = lines.0.len() + 4;
+	let cols = lines.0.iter().map(Vec::len).max().unwrap_or(0) + 4;
+	let around = |b: &TBox, ps: &[(Point, Direction)]| ps.iter().any(|(p, _)| b.contains(*p));
+	let shared: Vec<bool> = boxes
+		.iter()
+		.map(|b| around(b, starts) && around(b, ends))
+		.collect();
+	let boxed = |p: Point| (0..boxes.len()).any(|i| !shared[i] && boxes[i].contains(p));
+	let blank = |p: Point| lines.at(p).is_none_or(|c| c == ' ') && !boxed(p);
+	let open = |p: Point| p.row < rows && p.col < cols && blank(p);
+	// nothing around a corner but the path itself, since ' joins three ways
+	let clear = |p: Point| {
+		Direction::VALUES
+			.into_iter()
+			.all(|d| p.in_dir(d).is_none_or(blank))
+	};
+	let crowded = |p: Point| !clear(p) as usize * CROWDED;
+	// another line running across d that carries on past it
+	let crossable = |p: Point, d: Direction| {
+		let across = match d {
+			Direction::Up | Direction::Dn => Direction::Lt,
+			Direction::Lt | Direction::Rt => Direction::Up,
+		};
+		!boxes.iter().any(|b| b.contains(p))
+			&& lines
+				.at(p)
+				.is_some_and(|c| can_go(c, across) && can_go(c, across.rev()) && !can_go(c, d))
+	};
+	let h = |p: Point| {
+		ends.iter()
+			.map(|(e, _)| e.row.abs_diff(p.row) + e.col.abs_diff(p.col))
+			.min()
+			.unwrap_or(0)
+	};
+
+	let mut heap = BinaryHeap::new();
+	let mut cost: HashMap<State, usize> = HashMap::new();
+	let mut prev: HashMap<State, State> = HashMap::new();
+	for (p, d) in starts {
+		if !open(*p) {
+			continue;
+		}
+		let s = State {
+			row: p.row,
+			col: p.col,
+			dir: dir_index(*d),
+			straight: true,
+		};
+		cost.insert(s, 0);
+		heap.push(Reverse((h(*p), 0, s)));
+	}
+	while let Some(Reverse((_, g, s))) = heap.pop() {
+		if cost.get(&s).is_some_and(|c| *c < g) {
+			continue;
+		}
+		let p = Point::from((s.row, s.col));
+		let here = Direction::VALUES[s.dir as usize];
+		if ends.iter().any(|(e, d)| *e == p && d.rev() == here) {
+			let mut cells = vec![p];
+			let mut at = s;
+			while let Some(b) = prev.get(&at) {
+				let q = Point::from((b.row, b.col));
+				// fill in the cell a hop went over
+				let last = cells[cells.len() - 1];
+				if last.row.abs_diff(q.row) + last.col.abs_diff(q.col) == 2 {
+					let mid = ((last.row + q.row) / 2, (last.col + q.col) / 2);
+					cells.push(Point::from(mid));
+				}
+				cells.push(q);
+				at = *b;
+			}
+			cells.reverse();
+			return Some(path_corners(&cells));
+		}
+		for d in Direction::VALUES {
+			if d == here.rev() || (s.straight && d != here) {
+				continue;
+			}
+			let turn = d != here;
+			if turn && !clear(p) {
+				continue;
+			}
+			let q = match p.in_dir(d) {
+				Some(q) => q,
+				None => continue,
+			};
+			let (next, step) = if open(q) {
+				(
+					State {
+						row: q.row,
+						col: q.col,
+						dir: dir_index(d),
+						straight: false,
+					},
+					1 + crowded(q),
+				)
+			} else if !turn && crossable(q, d) {
+				// over the other line and at least a cell straight on after it
+				match q.in_dir(d).filter(|r| open(*r)) {
+					Some(r) => (
+						State {
+							row: r.row,
+							col: r.col,
+							dir: dir_index(d),
+							straight: true,
+						},
+						2 + CROSS,
+					),
+					None => continue,
+				}
+			} else {
+				continue;
+			};
+			let ng = g + step + turn as usize * TURN;
+			if cost.get(&next).is_none_or(|c| ng < *c) {
+				cost.insert(next, ng);
+				prev.insert(next, s);
+				heap.push(Reverse((
+					ng + h(Point::from((next.row, next.col))),
+					ng,
+					next,
+				)));
+			}
+		}
+	}
+	None
+}
+
+fn arrow(d: Direction) -> char {
+	match d {
+		Direction::Up => '^',
+		Direction::Dn => 'v',
+		Direction::Lt => '<',
+		Direction::Rt => '>',
+	}
+}
+
+// route a new edge from box a to box b and draw it, with an arrowhead at b if
+// head is set, returns the path drawn
+pub fn connect(
+	lines: &mut Lines,
+	boxes: &[TBox],
+	a: TBox,
+	b: TBox,
+	head: bool,
+) -> Option<Vec<Point>> {
+	// leaving from beside a corner would look like it came out of the corner
+	let sides = |b: TBox| -> Vec<(Point, Direction)> {
+		border(b)
+			.into_iter()
+			.filter(|(p, d)| match d {
+				Direction::Up | Direction::Dn => p.col > b.0.col && p.col < b.1.col,
+				Direction::Lt | Direction::Rt => p.row > b.0.row && p.row < b.1.row,
+			})
+			.collect()
+	};
+	let pth = route(lines, boxes, &sides(a), &sides(b))?;
+	let fst = pth[0];
+	let lst = pth[pth.len() - 1];
+	let out = border(a).into_iter().find(|(p, _)| *p == fst)?.1;
+	let into = border(b).into_iter().find(|(p, _)| *p == lst)?.1.rev();
+	let end = if head {
+		arrow(into)
+	} else {
+		corner(into, into)
+	};
+	draw_path(lines, &pth, (corner(out, out), end));
+	Some(pth)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn lines(s: &str) -> Lines {
+		Lines(s.lines().map(|l| l.chars().collect()).collect())
+	}
+
+	fn show(l: &Lines) -> String {
+		l.0.iter()
+			.map(|l| l.iter().collect::<String>().trim_end().to_string())
+			.collect::<Vec<_>>()
+			.join("\n")
+	}
+
+	#[test]
+	fn test_route_around() {
+		let mut l = lines(
+			r#"
+ ,---.        ,---.
+ | a |  ,--.  | b |
+ '---'  |c |  '---'
+        '--'
+"#,
+		);
+		let bs = boxes(&l);
+		// boxes come top left first, so c is after b
+		connect(&mut l, &bs, bs[0], bs[1], true).unwrap();
+		assert_eq!(
+			r#"
+ ,---.        ,---.
+ | a |  ,--.  | b |
+ '---'  |c |  '---'
+    |   '--'   ^
+    |          |
+    '----------'"#,
+			show(&l)
+		);
+		let es = edges(&l, &bs);
+		assert_eq!(1, es.len());
+		assert_eq!((Some(0), Some(1)), (es[0].from, es[0].to));
+		assert!(es[0].head.is_some());
+	}
+
+	#[test]
+	fn test_route_inside() {
+		let mut l = lines(
+			r#"
+ ,-------------------.
+ | ,---.       ,---. |
+ | | a |  ,--. | b | |
+ | '---'  |c | '---' |
+ |        '--'       |
+ |                   |
+ |                   |
+ '-------------------'
+"#,
+		);
+		let bs = boxes(&l);
+		let find = |tl: (usize, usize)| *bs.iter().find(|b| b.0 == Point::from(tl)).unwrap();
+		let (a, b) = (find((2, 3)), find((2, 15)));
+		connect(&mut l, &bs, a, b, true).unwrap();
+		assert_eq!(
+			r#"
+ ,-------------------.
+ | ,---.       ,---. |
+ | | a |  ,--. | b | |
+ | '---'  |c | '---' |
+ |    |   '--'  ^    |
+ |    '---------'    |
+ |                   |
+ '-------------------'"#,
+			show(&l)
+		);
+		let bs = boxes(&l);
+		let es = edges(&l, &bs);
+		assert_eq!(1, es.len());
+		let ends = (es[0].from.map(|i| bs[i]), es[0].to.map(|i| bs[i]));
+		assert_eq!((Some(a), Some(b)), ends);
+	}
+
+	#[test]
+	fn test_route_crossing() {
+		let mut l = lines(
+			r#"
+ ,---.   ,---.
+ | a |   | b |
+ '---'   '---'
+  |
+  '-------------
+      ,---.
+      | c |
+      '---'
+"#,
+		);
+		let bs = boxes(&l);
+		connect(&mut l, &bs, bs[1], bs[2], false).unwrap();
+		let es = edges(&l, &bs);
+		let mut ends: Vec<_> = es.iter().map(|e| (e.from, e.to)).collect();
+		ends.sort();
+		assert_eq!(vec![(Some(0), None), (Some(1), Some(2))], ends);
+	}
+}